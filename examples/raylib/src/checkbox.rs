@@ -6,12 +6,20 @@ use crate::*;
 const CHECKBOX_SIZE: Vec2 = Vec2::new(18, 18);
 const SPACING: u32 = 18;
 
-/// A UI demonstrating checkbox toggles.
+#[derive(Clone, Copy, PartialEq)]
+enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+/// A UI demonstrating checkbox toggles and a radio group.
 pub struct CheckboxUI {
     ui_state: reimui::UIState,
     font_info: RaylibFontInfo,
     music_on: bool,
     sfx_on: bool,
+    difficulty: Difficulty,
     show_debug: bool,
 }
 
@@ -22,13 +30,16 @@ impl CheckboxUI {
             font_info: RaylibFontInfo::new(rl),
             music_on: true,
             sfx_on: false,
+            difficulty: Difficulty::Normal,
             show_debug: false,
         }
     }
 
     /// Build reimui UI frame
     fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
-        let mut ui = UIContext::new(self.ui_state, &self.font_info, input_state);
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
+        // arrow keys also move focus between the checkboxes, like a gamepad menu
+        ui.set_directional_focus(true);
 
         ui.layout(LayoutDirection::Vertical, Some(SPACING), false, |ui| {
             ui.text_layout("Checkboxes".into());
@@ -39,15 +50,34 @@ impl CheckboxUI {
                 &mut self.music_on,
                 str.to_string(),
                 1.0,
-                100,
             );
 
+            // sfx can only be changed while music is on.
+            // left labels in one layout share a width, lining the checkboxes up
             let str = format!("SFX {}", if self.sfx_on { "on" } else { "off" });
-            ui.checkbox_layout_label_right(CHECKBOX_SIZE, &mut self.sfx_on, str.to_string(), 2.0, 100);
+            ui.with_disabled(!self.music_on, |ui| {
+                ui.checkbox_layout_label_left(CHECKBOX_SIZE, &mut self.sfx_on, str.to_string(), 2.0);
+            });
+
+            // radios sharing one value form a group, arrow keys move within it
+            ui.text_layout("Difficulty".into());
+            for (option, label) in [
+                (Difficulty::Easy, "Easy"),
+                (Difficulty::Normal, "Normal"),
+                (Difficulty::Hard, "Hard"),
+            ] {
+                ui.radio_layout_label_right(
+                    CHECKBOX_SIZE,
+                    &mut self.difficulty,
+                    option,
+                    label.into(),
+                    1.0,
+                );
+            }
         });
 
-        let ui_result = ui.end();
-        self.ui_state = ui_result.new_state;
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
         ui_result
     }
 
@@ -24,7 +24,7 @@ impl ClassListUI {
 
     /// Build reimui UI frame
     fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
-        let mut ui = UIContext::new(self.ui_state, &self.font_info, input_state);
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
 
         // The "panel" class colors the layout background in the renderer.
         ui.with_class_list(ClassList::new("panel"), |ui| {
@@ -37,7 +37,7 @@ impl ClassListUI {
 
                 ui.layout(LayoutDirection::Horizontal, Some(12), false, |ui| {
                     ui.with_class_list(ClassList::new("danger"), |ui| {
-                        if ui.button_layout(BUTTON_PADDING, "Danger action".into()) {
+                        if ui.button_layout(BUTTON_PADDING, "Danger action".into()).clicked {
                             self.danger_clicks += 1;
                         }
                     });
@@ -53,8 +53,8 @@ impl ClassListUI {
             });
         });
 
-        let ui_result = ui.end();
-        self.ui_state = ui_result.new_state;
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
 
         ui_result
     }
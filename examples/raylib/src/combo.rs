@@ -0,0 +1,92 @@
+use raylib::prelude::*;
+use reimui::prelude::*;
+
+use crate::*;
+
+const COMBO_SIZE: Vec2 = Vec2::new(160, 28);
+const BUTTON_PADDING: Vec2 = Vec2::new(10, 8);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Resolution {
+    Low,
+    Medium,
+    High,
+    Ultra,
+}
+
+/// A UI demonstrating combo boxes whose popups open over other widgets.
+pub struct ComboUI {
+    ui_state: reimui::UIState,
+    font_info: RaylibFontInfo,
+    resolution: Resolution,
+    language: &'static str,
+    applied: u32,
+}
+
+impl ComboUI {
+    pub fn new(rl: &RaylibHandle) -> Self {
+        Self {
+            ui_state: reimui::UIState::new(),
+            font_info: RaylibFontInfo::new(rl),
+            resolution: Resolution::Medium,
+            language: "English",
+            applied: 0,
+        }
+    }
+
+    /// Build reimui UI frame
+    fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
+
+        ui.layout(LayoutDirection::Vertical, Some(12), false, |ui| {
+            ui.text_layout(format!("Applied {} times", self.applied));
+
+            // enum options
+            ui.combo_box_layout(
+                COMBO_SIZE,
+                &mut self.resolution,
+                &[
+                    (Resolution::Low, "Low"),
+                    (Resolution::Medium, "Medium"),
+                    (Resolution::High, "High"),
+                    (Resolution::Ultra, "Ultra"),
+                ],
+            );
+
+            // string options
+            ui.combo_box_layout(
+                COMBO_SIZE,
+                &mut self.language,
+                &[
+                    ("English", "English"),
+                    ("Deutsch", "Deutsch"),
+                    ("Français", "Français"),
+                ],
+            );
+
+            // covered by the open popups, and not clickable through them
+            if ui.button_layout(BUTTON_PADDING, "Apply".into()).clicked {
+                self.applied += 1;
+            }
+        });
+
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
+        ui_result
+    }
+
+    pub fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let input_state = raylib_input_state(rl, &self.ui_state);
+        let ui_result = self.do_reimui(input_state);
+
+        let mut d = rl.begin_drawing(thread);
+        d.clear_background(Color::RAYWHITE);
+        apply_reimui_to_raylib(&ui_result, &mut d, &self.font_info);
+    }
+}
+
+impl SampleUI for ComboUI {
+    fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        self.draw(rl, thread);
+    }
+}
@@ -24,7 +24,7 @@ impl LayoutsUI {
 
     /// Build reimui UI frame
     fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
-        let mut ui = UIContext::new(self.ui_state, &self.font_info, input_state);
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
 
         // main layout - horizontal
         ui.layout(
@@ -56,7 +56,12 @@ impl LayoutsUI {
                     },
                 );
 
-                // another vertical layout with a toggle for the layouts
+                // another vertical layout, padded and centered, with a toggle for the layouts
+                ui.set_next_layout_style(LayoutStyle {
+                    padding: Insets::all(12),
+                    align: Align::Center,
+                    ..Default::default()
+                });
                 ui.layout(
                     LayoutDirection::Vertical,
                     Some(SPACING),
@@ -65,17 +70,49 @@ impl LayoutsUI {
                         if ui.button_layout(
                             BUTTON_PADDING,
                             "Toggle layouts".into()
-                        ) {
+                        ).clicked {
                             self.show_layouts = !self.show_layouts;
                         }
+                        ui.text_layout("Centered".into());
+
+                        // a row pushing its buttons to either end
+                        ui.set_next_layout_style(LayoutStyle {
+                            justify: Justify::SpaceBetween,
+                            min_size: Vec2::new(260, 0),
+                            ..Default::default()
+                        });
+                        ui.layout(LayoutDirection::Horizontal, None, self.show_layouts, |ui| {
+                            ui.button_layout(BUTTON_PADDING, "Start".into());
+                            ui.button_layout(BUTTON_PADDING, "End".into());
+                        });
+
+                        // a grid lining its labels and buttons up in columns
+                        let columns = [GridColumn::Auto, GridColumn::Fixed(120)];
+                        ui.grid(&columns, Vec2::new(SPACING, 10), self.show_layouts, |ui| {
+                            for (label, value) in [("Name", "reimui"), ("Layout direction", "Grid")] {
+                                ui.text_layout(label.into());
+                                ui.set_next_length(Length::Fill(1.0));
+                                ui.button_layout(BUTTON_PADDING, value.into());
+                            }
+                            ui.set_next_span(2);
+                            ui.set_next_length(Length::Fill(1.0));
+                            ui.button_layout(BUTTON_PADDING, "Spanning both columns".into());
+                        });
+
+                        // tags wrapping onto new rows as they fill the width
+                        ui.flow(260, Vec2::new(8, 8), self.show_layouts, |ui| {
+                            for tag in ["rust", "ui", "immediate mode", "raylib", "layouts", "widgets", "examples"] {
+                                ui.button_layout(BUTTON_PADDING, tag.into());
+                            }
+                        });
                     },
                 );
             },
         );
 
         // reassign the state and push the result back for raylib binding
-        let ui_result = ui.end();
-        self.ui_state = ui_result.new_state;
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
 
         ui_result
     }
@@ -2,9 +2,12 @@
 use raylib::prelude::*;
 pub mod checkbox;
 pub mod class_lists;
+pub mod combo;
 pub mod layouts;
+pub mod scroll;
 pub mod simple;
 pub mod slider;
+pub mod text_input;
 
 pub trait SampleUI {
     fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread);
@@ -40,9 +43,9 @@ pub fn color_palette(
     flags: reimui::flags::Flags,
     class_list: Option<reimui::ClassList>,
 ) -> Color {
-    let is_active = flags & reimui::flags::ACTIVE != 0;
-    let is_hover = flags & reimui::flags::HOVER != 0;
-    let is_focus = flags & reimui::flags::FOCUSED != 0;
+    let is_active = flags.contains(reimui::flags::ACTIVE);
+    let is_hover = flags.contains(reimui::flags::HOVER);
+    let is_focus = flags.contains(reimui::flags::FOCUSED);
     let has_class = |tag: &'static str| class_list.is_some_and(|cls| cls.has(tag));
     let mut color = match role {
         reimui::UIDrawRole::Text => {
@@ -91,6 +94,55 @@ pub fn color_palette(
         }
         reimui::UIDrawRole::CheckboxCheck => Color::DARKBLUE,
         reimui::UIDrawRole::LayoutBackground => Color::GREEN,
+        reimui::UIDrawRole::TextInputBackground => {
+            if is_focus {
+                Color::WHITE
+            } else if is_hover {
+                Color::LIGHTGRAY
+            } else {
+                Color::GRAY
+            }
+        }
+        reimui::UIDrawRole::TextInputText => Color::BLACK,
+        reimui::UIDrawRole::TextInputCaret => Color::DARKBLUE,
+        reimui::UIDrawRole::TextInputSelection => Color::SKYBLUE,
+        reimui::UIDrawRole::ScrollbarTrack => Color::LIGHTGRAY,
+        reimui::UIDrawRole::ScrollbarThumb => {
+            if is_active {
+                Color::DARKGRAY
+            } else {
+                Color::GRAY
+            }
+        }
+        reimui::UIDrawRole::RadioOuter => {
+            if is_focus {
+                Color::DARKGRAY
+            } else if is_hover {
+                Color::LIGHTGRAY
+            } else {
+                Color::GRAY
+            }
+        }
+        reimui::UIDrawRole::RadioDot => Color::DARKBLUE,
+        reimui::UIDrawRole::ComboBoxBackground => {
+            if is_focus || is_hover {
+                Color::LIGHTGRAY
+            } else {
+                Color::GRAY
+            }
+        }
+        reimui::UIDrawRole::ComboBoxText | reimui::UIDrawRole::PopupItemText => Color::BLACK,
+        reimui::UIDrawRole::ComboBoxArrow | reimui::UIDrawRole::ShortcutText => Color::DARKGRAY,
+        reimui::UIDrawRole::PopupBackground => Color::WHITE,
+        reimui::UIDrawRole::PopupItem => {
+            if is_hover {
+                Color::SKYBLUE
+            } else if flags.contains(reimui::flags::SELECTED) {
+                Color::LIGHTGRAY
+            } else {
+                Color::WHITE
+            }
+        }
     };
 
     if matches!(role, reimui::UIDrawRole::LayoutBackground) && has_class("panel") {
@@ -125,16 +177,44 @@ pub fn color_palette(
         }
     }
 
+    // disabled widgets are faded out
+    if flags.contains(reimui::flags::DISABLED) {
+        color = color.fade(0.4);
+    }
+
     color
 }
 
+/// A clip rect on screen, and the total scroll offset of the draws inside it
+#[derive(Clone, Copy)]
+struct ClipRegion {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    offset_x: i32,
+    offset_y: i32,
+}
+
+impl ClipRegion {
+    fn scissor(&self) {
+        // SAFETY: only called between begin_drawing and the end of the draw handle
+        unsafe { raylib::ffi::BeginScissorMode(self.x, self.y, self.w, self.h) };
+    }
+}
+
 /// Applies the result of a reimui draw to raylib
 pub fn apply_reimui_to_raylib(
     ui_result: &reimui::UIResult,
     d: &mut RaylibDrawHandle,
     font_info: &RaylibFontInfo,
 ) {
+    let mut clips: Vec<ClipRegion> = vec![];
     for command in &ui_result.commands {
+        // draws inside scrolled clips are shifted by the scroll offset
+        let (offset_x, offset_y) = clips
+            .last()
+            .map_or((0, 0), |clip| (clip.offset_x, clip.offset_y));
         match command {
             reimui::DrawCommand::DrawText {
                 content,
@@ -144,25 +224,98 @@ pub fn apply_reimui_to_raylib(
                 let font_size = ((font_info.font_size as f32) * text_scale).max(1.0);
                 d.draw_text(
                     content,
-                    draw_data.rect.top_left.x as i32,
-                    draw_data.rect.top_left.y as i32,
+                    draw_data.rect.top_left.x as i32 - offset_x,
+                    draw_data.rect.top_left.y as i32 - offset_y,
                     font_size.ceil() as i32,
                     color_palette(draw_data.role, draw_data.flags, draw_data.class_list),
                 );
             }
             reimui::DrawCommand::DrawRect { draw_data } => {
                 d.draw_rectangle(
-                    draw_data.rect.top_left.x as i32,
-                    draw_data.rect.top_left.y as i32,
+                    draw_data.rect.top_left.x as i32 - offset_x,
+                    draw_data.rect.top_left.y as i32 - offset_y,
                     draw_data.rect.size.x as i32,
                     draw_data.rect.size.y as i32,
                     color_palette(draw_data.role, draw_data.flags, draw_data.class_list),
                 );
             }
+            reimui::DrawCommand::PushClip { rect, offset } => {
+                // raylib scissors don't nest, so intersect with the enclosing clip ourselves
+                let mut clip = ClipRegion {
+                    x: rect.top_left.x as i32 - offset_x,
+                    y: rect.top_left.y as i32 - offset_y,
+                    w: rect.size.x as i32,
+                    h: rect.size.y as i32,
+                    offset_x: offset_x + offset.x as i32,
+                    offset_y: offset_y + offset.y as i32,
+                };
+                if let Some(parent) = clips.last() {
+                    let right = (clip.x + clip.w).min(parent.x + parent.w);
+                    let bottom = (clip.y + clip.h).min(parent.y + parent.h);
+                    clip.x = clip.x.max(parent.x);
+                    clip.y = clip.y.max(parent.y);
+                    clip.w = (right - clip.x).max(0);
+                    clip.h = (bottom - clip.y).max(0);
+                }
+                clip.scissor();
+                clips.push(clip);
+            }
+            reimui::DrawCommand::PopClip => {
+                clips.pop();
+                match clips.last() {
+                    Some(parent) => parent.scissor(),
+                    // SAFETY: matches a BeginScissorMode from a PushClip
+                    None => unsafe { raylib::ffi::EndScissorMode() },
+                }
+            }
         }
     }
 }
 
+/// raylib keys reported to reimui as key events
+const KEY_EVENT_MAP: [(KeyboardKey, reimui::Key); 40] = [
+    (KeyboardKey::KEY_A, reimui::Key::A),
+    (KeyboardKey::KEY_B, reimui::Key::B),
+    (KeyboardKey::KEY_C, reimui::Key::C),
+    (KeyboardKey::KEY_D, reimui::Key::D),
+    (KeyboardKey::KEY_E, reimui::Key::E),
+    (KeyboardKey::KEY_F, reimui::Key::F),
+    (KeyboardKey::KEY_G, reimui::Key::G),
+    (KeyboardKey::KEY_H, reimui::Key::H),
+    (KeyboardKey::KEY_I, reimui::Key::I),
+    (KeyboardKey::KEY_J, reimui::Key::J),
+    (KeyboardKey::KEY_K, reimui::Key::K),
+    (KeyboardKey::KEY_L, reimui::Key::L),
+    (KeyboardKey::KEY_M, reimui::Key::M),
+    (KeyboardKey::KEY_N, reimui::Key::N),
+    (KeyboardKey::KEY_O, reimui::Key::O),
+    (KeyboardKey::KEY_P, reimui::Key::P),
+    (KeyboardKey::KEY_Q, reimui::Key::Q),
+    (KeyboardKey::KEY_R, reimui::Key::R),
+    (KeyboardKey::KEY_S, reimui::Key::S),
+    (KeyboardKey::KEY_T, reimui::Key::T),
+    (KeyboardKey::KEY_U, reimui::Key::U),
+    (KeyboardKey::KEY_V, reimui::Key::V),
+    (KeyboardKey::KEY_W, reimui::Key::W),
+    (KeyboardKey::KEY_X, reimui::Key::X),
+    (KeyboardKey::KEY_Y, reimui::Key::Y),
+    (KeyboardKey::KEY_Z, reimui::Key::Z),
+    (KeyboardKey::KEY_TAB, reimui::Key::Tab),
+    (KeyboardKey::KEY_ENTER, reimui::Key::Enter),
+    (KeyboardKey::KEY_ESCAPE, reimui::Key::Escape),
+    (KeyboardKey::KEY_SPACE, reimui::Key::Space),
+    (KeyboardKey::KEY_BACKSPACE, reimui::Key::Backspace),
+    (KeyboardKey::KEY_DELETE, reimui::Key::Delete),
+    (KeyboardKey::KEY_LEFT, reimui::Key::Left),
+    (KeyboardKey::KEY_RIGHT, reimui::Key::Right),
+    (KeyboardKey::KEY_UP, reimui::Key::Up),
+    (KeyboardKey::KEY_DOWN, reimui::Key::Down),
+    (KeyboardKey::KEY_HOME, reimui::Key::Home),
+    (KeyboardKey::KEY_END, reimui::Key::End),
+    (KeyboardKey::KEY_PAGE_UP, reimui::Key::PageUp),
+    (KeyboardKey::KEY_PAGE_DOWN, reimui::Key::PageDown),
+];
+
 /// Collect the raylib input state and map it into reimui's expected input format.
 pub fn raylib_input_state(
     rl: &mut RaylibHandle,
@@ -183,19 +336,85 @@ pub fn raylib_input_state(
         else { reimui::ButtonState::Up }
     };
 
+    // shift+tab moves focus backwards
+    let tab = get_key_state(KeyboardKey::KEY_TAB);
+    let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+    let (focus_next_button, focus_prev_button) = if shift {
+        (reimui::ButtonState::Up, tab)
+    } else {
+        (tab, reimui::ButtonState::Up)
+    };
+
     let mut input = reimui::UIInputState {
         mouse_position: reimui::Vec2 {
             x: mouse.x.max(0.0) as u32,
             y: mouse.y.max(0.0) as u32,
         },
         activate_button: get_mouse_state(MouseButton::MOUSE_BUTTON_LEFT),
-        focus_next_button: get_key_state(KeyboardKey::KEY_TAB),
+        focus_next_button,
+        focus_prev_button,
+        cancel_button: get_key_state(KeyboardKey::KEY_ESCAPE),
+        secondary_button: get_mouse_state(MouseButton::MOUSE_BUTTON_RIGHT),
+        middle_button: get_mouse_state(MouseButton::MOUSE_BUTTON_MIDDLE),
         move_left: get_key_state(KeyboardKey::KEY_LEFT),
         move_right: get_key_state(KeyboardKey::KEY_RIGHT),
         move_up: get_key_state(KeyboardKey::KEY_UP),
         move_down: get_key_state(KeyboardKey::KEY_DOWN),
+        backspace_button: get_key_state(KeyboardKey::KEY_BACKSPACE),
+        delete_button: get_key_state(KeyboardKey::KEY_DELETE),
+        home_button: get_key_state(KeyboardKey::KEY_HOME),
+        end_button: get_key_state(KeyboardKey::KEY_END),
+        page_up_button: get_key_state(KeyboardKey::KEY_PAGE_UP),
+        page_down_button: get_key_state(KeyboardKey::KEY_PAGE_DOWN),
+        wheel_x: rl.get_mouse_wheel_move_v().x,
+        wheel_y: rl.get_mouse_wheel_move_v().y,
+        time: rl.get_time(),
+        ..Default::default()
+    };
+
+    // Held editing keys repeat, so report repeats as fresh presses.
+    for (key, state) in [
+        (KeyboardKey::KEY_BACKSPACE, &mut input.backspace_button),
+        (KeyboardKey::KEY_DELETE, &mut input.delete_button),
+        (KeyboardKey::KEY_LEFT, &mut input.move_left),
+        (KeyboardKey::KEY_RIGHT, &mut input.move_right),
+        (KeyboardKey::KEY_UP, &mut input.move_up),
+        (KeyboardKey::KEY_DOWN, &mut input.move_down),
+    ] {
+        if rl.is_key_pressed_repeat(key) {
+            *state = reimui::ButtonState::Down;
+        }
+    }
+
+    let either_down = |left, right| rl.is_key_down(left) || rl.is_key_down(right);
+    input.modifiers = reimui::Modifiers {
+        shift,
+        ctrl: either_down(KeyboardKey::KEY_LEFT_CONTROL, KeyboardKey::KEY_RIGHT_CONTROL),
+        alt: either_down(KeyboardKey::KEY_LEFT_ALT, KeyboardKey::KEY_RIGHT_ALT),
+        meta: either_down(KeyboardKey::KEY_LEFT_SUPER, KeyboardKey::KEY_RIGHT_SUPER),
     };
 
+    // the named buttons are set above, key events report every mapped key with its modifiers
+    for (rl_key, key) in KEY_EVENT_MAP {
+        let repeat = rl.is_key_pressed_repeat(rl_key);
+        let event = |pressed| reimui::KeyEvent {
+            key,
+            pressed,
+            repeat,
+            modifiers: input.modifiers,
+        };
+        if rl.is_key_pressed(rl_key) || repeat {
+            input.key_events.push(event(true));
+        }
+        if rl.is_key_released(rl_key) {
+            input.key_events.push(event(false));
+        }
+    }
+
+    while let Some(c) = rl.get_char_pressed() {
+        input.typed_chars.push(c);
+    }
+
     // Allow pressing enter to "click" the currently focused control.
     let enter_state = if rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_KP_ENTER) {
         reimui::ButtonState::Down
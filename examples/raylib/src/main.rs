@@ -1,6 +1,8 @@
 use std::{env, process::ExitCode};
 
-use reimui_raylib_example::{SampleUI, checkbox, class_lists, layouts, simple, slider};
+use reimui_raylib_example::{
+    SampleUI, checkbox, class_lists, combo, layouts, scroll, simple, slider, text_input,
+};
 
 /// Simple example runner - actual code may be found inside the relevant file
 fn main() -> ExitCode {
@@ -8,6 +10,8 @@ fn main() -> ExitCode {
         .size(640, 400)
         .title("reimui + raylib")
         .build();
+    // escape closes popups instead of the window
+    rl.set_exit_key(None);
 
     // figure out what example to run from the arg or run 'simple'
     let run_flag = env::args().nth(1);
@@ -26,6 +30,9 @@ fn main() -> ExitCode {
         "layouts" => &mut layouts::LayoutsUI::new(&rl),
         "class_lists" => &mut class_lists::ClassListUI::new(&rl),
         "checkbox" => &mut checkbox::CheckboxUI::new(&rl),
+        "text_input" => &mut text_input::TextInputUI::new(&rl),
+        "scroll" => &mut scroll::ScrollUI::new(&rl),
+        "combo" => &mut combo::ComboUI::new(&rl),
         other => {
             println!("unknown example type: '{}'", other);
             return ExitCode::FAILURE;
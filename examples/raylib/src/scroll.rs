@@ -0,0 +1,78 @@
+use raylib::prelude::*;
+use reimui::ScrollState;
+use reimui::prelude::*;
+
+use crate::*;
+
+const LIST_SIZE: Vec2 = Vec2::new(220, 240);
+const BUTTON_PADDING: Vec2 = Vec2::new(10, 8);
+
+/// A UI demonstrating a scroll area holding more content than fits.
+pub struct ScrollUI {
+    ui_state: reimui::UIState,
+    font_info: RaylibFontInfo,
+    list_scroll: ScrollState,
+    picked: Option<u32>,
+}
+
+impl ScrollUI {
+    pub fn new(rl: &RaylibHandle) -> Self {
+        Self {
+            ui_state: reimui::UIState::new(),
+            font_info: RaylibFontInfo::new(rl),
+            list_scroll: ScrollState::default(),
+            picked: None,
+        }
+    }
+
+    /// Build reimui UI frame
+    fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
+
+        ui.layout(LayoutDirection::Vertical, Some(12), false, |ui| {
+            let picked = match self.picked {
+                Some(i) => format!("Picked item {}", i),
+                None => "Pick an item".into(),
+            };
+            ui.text_layout(picked);
+
+            // use the wheel, drag the scrollbar or tab through the items
+            ui.scroll_area_layout(LIST_SIZE, &mut self.list_scroll, |ui| {
+                for i in 0..30 {
+                    let item = ui.button_layout(BUTTON_PADDING, format!("Item {}", i));
+                    if item.clicked {
+                        self.picked = Some(i);
+                    }
+                    // right click an item for more
+                    ui.context_menu(&item, 120, |ui| {
+                        if ui.menu_item("Pick".into()).clicked {
+                            self.picked = Some(i);
+                        }
+                        if ui.menu_item("Clear pick".into()).clicked {
+                            self.picked = None;
+                        }
+                    });
+                }
+            });
+        });
+
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
+        ui_result
+    }
+
+    pub fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let input_state = raylib_input_state(rl, &self.ui_state);
+        let ui_result = self.do_reimui(input_state);
+
+        let mut d = rl.begin_drawing(thread);
+        d.clear_background(Color::RAYWHITE);
+        apply_reimui_to_raylib(&ui_result, &mut d, &self.font_info);
+    }
+}
+
+impl SampleUI for ScrollUI {
+    fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        self.draw(rl, thread);
+    }
+}
@@ -5,9 +5,11 @@ use crate::*;
 
 const BUTTON_PADDING: Vec2 = Vec2 { x: 16, y: 12 };
 
-/// A simple reimui layout with a few pieces of text and a button with a click counter.
+/// A simple reimui layout with a few pieces of text and a button with a click counter,
+/// which can be reset through a confirmation dialog, also with keyboard shortcuts.
 pub struct SimpleUI {
     clicked: u32,
+    confirming_reset: bool,
     ui_state: reimui::UIState,
     font_info: RaylibFontInfo,
 }
@@ -16,6 +18,7 @@ impl SimpleUI {
     pub fn new(rl: &RaylibHandle) -> Self {
         Self {
             clicked: 0,
+            confirming_reset: false,
             ui_state: reimui::UIState::new(),
             font_info: RaylibFontInfo::new(rl),
         }
@@ -23,7 +26,7 @@ impl SimpleUI {
 
     /// Build reimui UI frame
     fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
-        let mut ui = UIContext::new(self.ui_state, &self.font_info, input_state);
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
 
         // put some text somewhere specific
         let pos = Vec2::new(364, 298);
@@ -33,15 +36,43 @@ impl SimpleUI {
         ui.layout(LayoutDirection::Vertical, Some(25), false, |ui| {
             ui.text_layout_scaled("reimui + raylib".into(), 5.0);
             ui.text_layout("Immediate mode UI rendering to raylib".into());
-            let clicked = ui.button_layout(BUTTON_PADDING, format!("Click me {}", self.clicked));
+            let response = ui.button_layout(BUTTON_PADDING, format!("Click me {}", self.clicked));
 
-            if clicked {
+            // double clicks count twice
+            if response.double_clicked {
                 self.clicked += 1;
             }
+            if response.clicked {
+                self.clicked += 1;
+            }
+            // the dialog's own shortcuts take over while it's open
+            ui.set_next_shortcut(Shortcut::command(Key::R));
+            if ui.button_layout(BUTTON_PADDING, "Reset".into()).clicked {
+                self.confirming_reset = true;
+            }
         });
+
+        // the dialog keeps keyboard focus to itself while open, and hands it back when closed
+        if self.confirming_reset {
+            ui.with_layer(Layer::Popup, |ui| {
+                ui.with_focus_scope(true, |ui| {
+                    let pos = Vec2::new(364, 400);
+                    ui.text_at(format!("Reset {} clicks?", self.clicked), pos);
+                    ui.set_next_shortcut(Shortcut::new(Modifiers::NONE, Key::Y));
+                    if ui.button(Vec2::new(pos.x, pos.y + 30), BUTTON_PADDING, "Reset".into()).clicked {
+                        self.clicked = 0;
+                        self.confirming_reset = false;
+                    }
+                    ui.set_next_shortcut(Shortcut::new(Modifiers::NONE, Key::N));
+                    if ui.button(Vec2::new(pos.x + 140, pos.y + 30), BUTTON_PADDING, "Cancel".into()).clicked {
+                        self.confirming_reset = false;
+                    }
+                });
+            });
+        }
         // reassign the state and push the result back for raylib binding
-        let ui_result = ui.end();
-        self.ui_state = ui_result.new_state;
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
 
         ui_result
     }
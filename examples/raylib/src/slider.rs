@@ -4,7 +4,8 @@ use reimui::prelude::*;
 use crate::*;
 
 // Slider sizes can be configured independently of their ranges
-const BIG_SLIDER_SIZE: Vec2 = Vec2::new(100, 50);
+const BIG_SLIDER_HEIGHT: u32 = 50;
+const BIG_SLIDER_ROW_WIDTH: u32 = 400;
 const SMALL_SLIDER_SIZE: Vec2 = Vec2::new(40, 10);
 
 /// A UI demonstrating sliders
@@ -27,42 +28,48 @@ impl SliderUI {
 
     /// Build reimui UI frame
     fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
-        let mut ui = UIContext::new(self.ui_state, &self.font_info, input_state);
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
 
         // build a vertical layout
         ui.layout(LayoutDirection::Vertical, Some(25), false, |ui| {
             ui.text_layout("sliders".into());
 
+            // a fixed width row, the slider filling what its label leaves
             let a_val = format!("{}", self.slider_a_state.value);
-            if ui.slider_layout_label_right(
-                BIG_SLIDER_SIZE,
-                &mut self.slider_a_state,
-                a_val,
-                1.0,
-                50,
-            ) {
-                println!("slider moved!");
-            }
+            ui.set_next_layout_style(LayoutStyle {
+                align: Align::Center,
+                width: Some(BIG_SLIDER_ROW_WIDTH),
+                ..Default::default()
+            });
+            ui.layout(LayoutDirection::Horizontal, Some(10), false, |ui| {
+                ui.set_next_length(Length::Fill(1.0));
+                if ui
+                    .slider_layout(Vec2::new(0, BIG_SLIDER_HEIGHT), &mut self.slider_a_state)
+                    .changed
+                {
+                    println!("slider moved!");
+                }
+                ui.text_layout(a_val);
+            });
 
             // Note that we pad the float value here to ensure the text size is consistent.
             // If we didn't, the slider would shift as the number of digits changed.
             // The format specifier here ensures 1 decimal place and a width of 4 characters.
-            // We pad the value for visual consistency, but we also use a fixed label width.
-            // Variable width fonts mean that even padded strings can change width, shifting the slider.
-            // Since focus relies on a stable Rect, shifting causes focus loss.
+            // Left labels share the width of the widest label in the layout, measured over the
+            // last frame, but variable width fonts mean that even padded strings can change width,
+            // shifting the slider. Focus and dragging follow the widget's ID, so a shift is only cosmetic.
             let b_val = format!("{:4.1}", self.slider_b_state.value);
             ui.slider_layout_label_left(
                 SMALL_SLIDER_SIZE,
                 &mut self.slider_b_state,
                 b_val,
                 1.0,
-                50,
             );
         });
 
         // reassign the state and push the result back for raylib binding
-        let ui_result = ui.end();
-        self.ui_state = ui_result.new_state;
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
 
         ui_result
     }
@@ -0,0 +1,70 @@
+use raylib::prelude::*;
+use reimui::TextInputState;
+use reimui::prelude::*;
+
+use crate::*;
+
+const FIELD_SIZE: Vec2 = Vec2::new(240, 28);
+const SPACING: u32 = 18;
+
+/// A UI demonstrating single line text fields.
+pub struct TextInputUI {
+    ui_state: reimui::UIState,
+    font_info: RaylibFontInfo,
+    name: TextInputState,
+    search: TextInputState,
+    autofocused: bool,
+}
+
+impl TextInputUI {
+    pub fn new(rl: &RaylibHandle) -> Self {
+        Self {
+            ui_state: reimui::UIState::new(),
+            font_info: RaylibFontInfo::new(rl),
+            name: TextInputState::new("reimui"),
+            search: TextInputState::default(),
+            autofocused: false,
+        }
+    }
+
+    /// Build reimui UI frame
+    fn do_reimui(&mut self, input_state: reimui::UIInputState) -> reimui::UIResult {
+        let mut ui = UIContext::new(std::mem::take(&mut self.ui_state), &self.font_info, input_state);
+
+        ui.layout(LayoutDirection::Vertical, Some(SPACING), false, |ui| {
+            ui.text_layout("Text input".into());
+
+            ui.text_input_layout(FIELD_SIZE, &mut self.name);
+            // start with the name field focused
+            if !self.autofocused
+                && let Some(id) = ui.last_widget_id()
+            {
+                ui.request_focus(id);
+                self.autofocused = true;
+            }
+            ui.text_layout(format!("Hello, {}!", self.name.buffer));
+
+            ui.text_input_layout(FIELD_SIZE, &mut self.search);
+            ui.text_layout(format!("Searching for '{}'", self.search.buffer));
+        });
+
+        let mut ui_result = ui.end();
+        self.ui_state = std::mem::take(&mut ui_result.new_state);
+        ui_result
+    }
+
+    pub fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let input_state = raylib_input_state(rl, &self.ui_state);
+        let ui_result = self.do_reimui(input_state);
+
+        let mut d = rl.begin_drawing(thread);
+        d.clear_background(Color::RAYWHITE);
+        apply_reimui_to_raylib(&ui_result, &mut d, &self.font_info);
+    }
+}
+
+impl SampleUI for TextInputUI {
+    fn draw(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        self.draw(rl, thread);
+    }
+}
@@ -1,23 +1,158 @@
 // Stupidly simple render-agnostic immediate mode UI lib
 
 use crate::flags::Flags;
-use std::{collections::VecDeque, ops::Range};
+use std::{
+    any::TypeId,
+    collections::{HashMap, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+};
 
 pub mod prelude {
     pub use super::{
-        ButtonState, FontInformation, Layout, LayoutDirection, Rect, UIContext, UIDrawRole,
-        UIInputState, UIState, Vec2,
+        Align, ButtonState, ChildStyle, FontInformation, GridColumn, Insets, Justify, Key,
+        KeyEvent, Layer, Layout, LayoutDirection, LayoutStyle, Length, Modifiers, Rect, Response,
+        Shortcut, UIContext, UIDrawRole, UIInputState, UIState, Vec2, WidgetId,
     };
 }
 
 #[rustfmt::skip]
 pub mod flags {
-    pub type Flags = u32;
-    pub const NONE: Flags           = 0;
-    pub const HOVER: Flags          = 1 << 0;
-    pub const DISABLED: Flags       = 1 << 1;
-    pub const ACTIVE: Flags         = 1 << 2;
-    pub const FOCUSED: Flags        = 1 << 2;
+    use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+
+    pub const NONE: Flags       = Flags::NONE;
+    pub const HOVER: Flags      = Flags::HOVER;
+    pub const DISABLED: Flags   = Flags::DISABLED;
+    pub const ACTIVE: Flags     = Flags::ACTIVE;
+    pub const FOCUSED: Flags    = Flags::FOCUSED;
+    pub const CHECKED: Flags    = Flags::CHECKED;
+    pub const SELECTED: Flags   = Flags::SELECTED;
+    pub const OPEN: Flags       = Flags::OPEN;
+    pub const DRAGGING: Flags   = Flags::DRAGGING;
+
+    /// A set of widget states attached to each draw, combined with `|`
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Flags(u32);
+
+    impl Flags {
+        pub const NONE: Flags       = Flags(0);
+        /// The mouse is over the widget
+        pub const HOVER: Flags      = Flags(1 << 0);
+        pub const DISABLED: Flags   = Flags(1 << 1);
+        /// The widget is being pressed
+        pub const ACTIVE: Flags     = Flags(1 << 2);
+        /// The widget holds keyboard focus
+        pub const FOCUSED: Flags    = Flags(1 << 3);
+        /// A checkbox is ticked or a radio is chosen
+        pub const CHECKED: Flags    = Flags(1 << 4);
+        /// The entry of a list that is the current value
+        pub const SELECTED: Flags   = Flags(1 << 5);
+        /// A combo box's popup is showing
+        pub const OPEN: Flags       = Flags(1 << 6);
+        /// The widget was pressed and is still held, such as a slider's knob or a held button
+        pub const DRAGGING: Flags   = Flags(1 << 7);
+
+        const NAMED: [(Flags, &'static str); 8] = [
+            (Flags::HOVER, "HOVER"),
+            (Flags::DISABLED, "DISABLED"),
+            (Flags::ACTIVE, "ACTIVE"),
+            (Flags::FOCUSED, "FOCUSED"),
+            (Flags::CHECKED, "CHECKED"),
+            (Flags::SELECTED, "SELECTED"),
+            (Flags::OPEN, "OPEN"),
+            (Flags::DRAGGING, "DRAGGING"),
+        ];
+    }
+
+    impl Flags {
+        pub const fn bits(self) -> u32 {
+            self.0
+        }
+
+        pub const fn is_empty(self) -> bool {
+            self.0 == 0
+        }
+
+        /// True if every flag in `other` is set
+        pub const fn contains(self, other: Flags) -> bool {
+            self.0 & other.0 == other.0
+        }
+
+        /// True if any flag in `other` is set
+        pub const fn intersects(self, other: Flags) -> bool {
+            self.0 & other.0 != 0
+        }
+
+        pub fn insert(&mut self, other: Flags) {
+            self.0 |= other.0;
+        }
+
+        pub fn remove(&mut self, other: Flags) {
+            self.0 &= !other.0;
+        }
+
+        /// Inserts `other` if `value`, removes it otherwise
+        pub fn set(&mut self, other: Flags, value: bool) {
+            if value {
+                self.insert(other);
+            } else {
+                self.remove(other);
+            }
+        }
+
+        /// Each single flag that is set, lowest bit first
+        pub fn iter(self) -> impl Iterator<Item = Flags> {
+            Self::NAMED
+                .into_iter()
+                .map(|(flag, _)| flag)
+                .filter(move |flag| self.contains(*flag))
+        }
+    }
+
+    impl std::fmt::Debug for Flags {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if self.is_empty() {
+                return write!(f, "NONE");
+            }
+            let mut names = Self::NAMED
+                .into_iter()
+                .filter(|(flag, _)| self.contains(*flag))
+                .map(|(_, name)| name);
+            if let Some(first) = names.next() {
+                write!(f, "{first}")?;
+            }
+            for name in names {
+                write!(f, " | {name}")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl BitOr for Flags {
+        type Output = Flags;
+        fn bitor(self, rhs: Flags) -> Flags {
+            Flags(self.0 | rhs.0)
+        }
+    }
+
+    impl BitOrAssign for Flags {
+        fn bitor_assign(&mut self, rhs: Flags) {
+            self.0 |= rhs.0;
+        }
+    }
+
+    impl BitAnd for Flags {
+        type Output = Flags;
+        fn bitand(self, rhs: Flags) -> Flags {
+            Flags(self.0 & rhs.0)
+        }
+    }
+
+    impl BitAndAssign for Flags {
+        fn bitand_assign(&mut self, rhs: Flags) {
+            self.0 &= rhs.0;
+        }
+    }
 }
 
 /// Something that can be used as a slider value.
@@ -72,6 +207,182 @@ where
     }
 }
 
+/// Caller-held state of an editable text field.
+/// `caret` and `selection_anchor` are byte indices into `buffer`, kept on char boundaries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextInputState {
+    pub buffer: String,
+    pub caret: usize,
+    /// The other end of the selection, the caret being the end that moves
+    pub selection_anchor: Option<usize>,
+    /// Horizontal scroll of the text in pixels, maintained by single line fields
+    pub scroll_x: u32,
+    /// Vertical scroll of the text in pixels, maintained by multi-line fields
+    pub scroll_y: u32,
+}
+
+impl TextInputState {
+    /// Creates a state holding `text` with the caret at the end
+    pub fn new(text: impl Into<String>) -> Self {
+        let buffer = text.into();
+        Self {
+            caret: buffer.len(),
+            buffer,
+            selection_anchor: None,
+            scroll_x: 0,
+            scroll_y: 0,
+        }
+    }
+
+    /// The selected byte range, if the selection is not empty
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.selection_anchor?;
+        if anchor == self.caret {
+            return None;
+        }
+        Some(anchor.min(self.caret)..anchor.max(self.caret))
+    }
+
+    pub fn selected_text(&self) -> &str {
+        self.selection().map_or("", |range| &self.buffer[range])
+    }
+
+    pub fn select_all(&mut self) {
+        self.selection_anchor = Some(0);
+        self.caret = self.buffer.len();
+    }
+
+    /// Moves the caret to `idx`, extending the selection from the old caret if `select` is set
+    pub fn set_caret(&mut self, idx: usize, select: bool) {
+        if select {
+            self.selection_anchor.get_or_insert(self.caret);
+        } else {
+            self.selection_anchor = None;
+        }
+        self.caret = floor_char_boundary(&self.buffer, idx);
+    }
+
+    /// Moves the caret one char left, or collapses the selection to its start
+    pub fn move_left(&mut self, select: bool) {
+        match self.selection() {
+            Some(range) if !select => self.set_caret(range.start, false),
+            _ => self.set_caret(prev_char_boundary(&self.buffer, self.caret), select),
+        }
+    }
+
+    /// Moves the caret one char right, or collapses the selection to its end
+    pub fn move_right(&mut self, select: bool) {
+        match self.selection() {
+            Some(range) if !select => self.set_caret(range.end, false),
+            _ => self.set_caret(next_char_boundary(&self.buffer, self.caret), select),
+        }
+    }
+
+    /// Replaces the selection, or inserts at the caret
+    pub fn insert_str(&mut self, text: &str) {
+        self.delete_selection();
+        self.buffer.insert_str(self.caret, text);
+        self.caret += text.len();
+    }
+
+    /// Deletes the selection or the char before the caret.
+    /// Returns true if anything was deleted.
+    pub fn delete_backward(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        let start = prev_char_boundary(&self.buffer, self.caret);
+        self.buffer.replace_range(start..self.caret, "");
+        let deleted = start != self.caret;
+        self.caret = start;
+        deleted
+    }
+
+    /// Deletes the selection or the char after the caret.
+    /// Returns true if anything was deleted.
+    pub fn delete_forward(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        let end = next_char_boundary(&self.buffer, self.caret);
+        self.buffer.replace_range(self.caret..end, "");
+        end != self.caret
+    }
+
+    /// Returns true if there was a selection to delete
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.selection_anchor = None;
+        match selection {
+            Some(range) => {
+                self.caret = range.start;
+                self.buffer.replace_range(range, "");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Puts the caret and anchor back on valid char boundaries if the buffer was changed externally
+    fn sanitize(&mut self) {
+        self.caret = floor_char_boundary(&self.buffer, self.caret);
+        self.selection_anchor = self
+            .selection_anchor
+            .map(|anchor| floor_char_boundary(&self.buffer, anchor));
+    }
+}
+
+fn floor_char_boundary(text: &str, idx: usize) -> usize {
+    let mut idx = idx.min(text.len());
+    while !text.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+fn prev_char_boundary(text: &str, idx: usize) -> usize {
+    text[..idx].char_indices().next_back().map_or(0, |(i, _)| i)
+}
+
+/// The scroll offset closest to `offset` that shows the span `start..end` in a view of `view_len`,
+/// clamped to `0..=max`. The span is relative to the unscrolled view and may start before it.
+fn scrolled_into_view(offset: u32, view_len: u32, start: i64, end: i64, max: u32) -> u32 {
+    let offset = i64::from(offset);
+    let scrolled = if start < offset {
+        start
+    } else if end > offset + i64::from(view_len) {
+        (end - i64::from(view_len)).min(start)
+    } else {
+        offset
+    };
+    scrolled.clamp(0, i64::from(max)) as u32
+}
+
+/// Index of the visual line containing `caret`.
+/// A caret on a soft wrap belongs to the start of the following line.
+fn line_of(lines: &[Range<usize>], caret: usize) -> usize {
+    lines
+        .iter()
+        .rposition(|line| line.start <= caret)
+        .unwrap_or_default()
+}
+
+fn next_char_boundary(text: &str, idx: usize) -> usize {
+    text[idx..]
+        .chars()
+        .next()
+        .map_or(idx, |c| idx + c.len_utf8())
+}
+
+/// Caller-held state of a scroll area
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrollState {
+    /// How far the content is scrolled, in pixels
+    pub offset: Vec2,
+    /// Size of the content as of the last draw
+    pub content_size: Vec2,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ButtonState {
     Down,     // Just pressed
@@ -86,7 +397,163 @@ impl ButtonState {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+/// Modifier keys held down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// Cmd on macOS, the Windows/super key elsewhere
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+        alt: false,
+        meta: false,
+    };
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ..Self::NONE
+    };
+    pub const CTRL: Self = Self {
+        ctrl: true,
+        ..Self::NONE
+    };
+    pub const ALT: Self = Self {
+        alt: true,
+        ..Self::NONE
+    };
+    pub const META: Self = Self {
+        meta: true,
+        ..Self::NONE
+    };
+    /// The platform's command modifier, see `command`
+    pub const COMMAND: Self = if cfg!(target_os = "macos") {
+        Self::META
+    } else {
+        Self::CTRL
+    };
+
+    /// The platform's command modifier: Cmd on macOS, Ctrl elsewhere
+    pub fn command(&self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.meta
+        } else {
+            self.ctrl
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// Combines two sets of modifiers, e.g. `Modifiers::CTRL.with(Modifiers::SHIFT)`
+    pub const fn with(self, other: Self) -> Self {
+        Self {
+            shift: self.shift || other.shift,
+            ctrl: self.ctrl || other.ctrl,
+            alt: self.alt || other.alt,
+            meta: self.meta || other.meta,
+        }
+    }
+}
+
+/// A physical key, named by its US layout meaning
+#[rustfmt::skip]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    A, B, C, D, E, F, G, H, I, J, K, L, M,
+    N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Left, Right, Up, Down,
+    Home, End, PageUp, PageDown,
+    Backspace, Delete, Insert,
+    Tab, Enter, Escape, Space,
+}
+
+impl Key {
+    /// Name of the key as shown in shortcut labels
+    #[rustfmt::skip]
+    pub fn name(&self) -> &'static str {
+        use Key::*;
+        match self {
+            A => "A", B => "B", C => "C", D => "D", E => "E", F => "F", G => "G",
+            H => "H", I => "I", J => "J", K => "K", L => "L", M => "M", N => "N",
+            O => "O", P => "P", Q => "Q", R => "R", S => "S", T => "T", U => "U",
+            V => "V", W => "W", X => "X", Y => "Y", Z => "Z",
+            Num0 => "0", Num1 => "1", Num2 => "2", Num3 => "3", Num4 => "4",
+            Num5 => "5", Num6 => "6", Num7 => "7", Num8 => "8", Num9 => "9",
+            F1 => "F1", F2 => "F2", F3 => "F3", F4 => "F4", F5 => "F5", F6 => "F6",
+            F7 => "F7", F8 => "F8", F9 => "F9", F10 => "F10", F11 => "F11", F12 => "F12",
+            Left => "Left", Right => "Right", Up => "Up", Down => "Down",
+            Home => "Home", End => "End", PageUp => "PgUp", PageDown => "PgDn",
+            Backspace => "Backspace", Delete => "Del", Insert => "Ins",
+            Tab => "Tab", Enter => "Enter", Escape => "Esc", Space => "Space",
+        }
+    }
+}
+
+/// A key going down or up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub key: Key,
+    /// True when the key went down, false when it was released
+    pub pressed: bool,
+    /// A press repeated by the key being held
+    pub repeat: bool,
+    /// Modifiers held when the event happened
+    pub modifiers: Modifiers,
+}
+
+/// A key combination triggering an action, see `UIContext::shortcut`.
+/// Displays as its accelerator text, e.g. "Ctrl+Shift+S".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub modifiers: Modifiers,
+    pub key: Key,
+}
+
+impl Shortcut {
+    pub const fn new(modifiers: Modifiers, key: Key) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// The key with the platform's command modifier, e.g. Ctrl+S, or Cmd+S on macOS
+    pub const fn command(key: Key) -> Self {
+        Self::new(Modifiers::COMMAND, key)
+    }
+
+    /// True if `event` presses (or repeats) this key with exactly these modifiers
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        event.pressed && event.key == self.key && event.modifiers == self.modifiers
+    }
+}
+
+impl std::fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let meta = if cfg!(target_os = "macos") {
+            "Cmd"
+        } else {
+            "Meta"
+        };
+        let held = [
+            (self.modifiers.ctrl, "Ctrl"),
+            (self.modifiers.alt, "Alt"),
+            (self.modifiers.shift, "Shift"),
+            (self.modifiers.meta, meta),
+        ];
+        for (_, name) in held.iter().filter(|(down, _)| *down) {
+            write!(f, "{name}+")?;
+        }
+        f.write_str(self.key.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub struct Vec2 {
     pub x: u32,
     pub y: u32,
@@ -123,6 +590,55 @@ impl Vec2 {
             y: a.y / b,
         }
     }
+
+    /// Component-wise maximum
+    pub fn max_cmp(a: Vec2, b: Vec2) -> Self {
+        Vec2 {
+            x: a.x.max(b.x),
+            y: a.y.max(b.y),
+        }
+    }
+}
+
+/// Space along each edge of a rect, such as padding or margins
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct Insets {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl Insets {
+    pub const ZERO: Self = Self::all(0);
+
+    pub const fn new(left: u32, top: u32, right: u32, bottom: u32) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    pub const fn all(inset: u32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    /// `x` on the left and right, `y` on the top and bottom
+    pub const fn symmetric(x: u32, y: u32) -> Self {
+        Self::new(x, y, x, y)
+    }
+
+    /// Space along the left and top edges
+    pub fn start(&self) -> Vec2 {
+        Vec2::new(self.left, self.top)
+    }
+
+    /// Total space across and down
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.left + self.right, self.top + self.bottom)
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
@@ -138,17 +654,85 @@ impl Rect {
             && point.y >= self.top_left.y
             && point.y <= self.top_left.y + self.size.y
     }
+
+    /// The overlapping area of both rects, empty if they don't overlap
+    pub fn intersect(&self, other: Rect) -> Rect {
+        let top_left = Vec2::new(
+            self.top_left.x.max(other.top_left.x),
+            self.top_left.y.max(other.top_left.y),
+        );
+        let bottom_right = Vec2::new(
+            (self.top_left.x + self.size.x).min(other.top_left.x + other.size.x),
+            (self.top_left.y + self.size.y).min(other.top_left.y + other.size.y),
+        );
+        Rect {
+            top_left,
+            size: Vec2::new(
+                bottom_right.x.saturating_sub(top_left.x),
+                bottom_right.y.saturating_sub(top_left.y),
+            ),
+        }
+    }
 }
 
 impl From<Layout> for Rect {
     fn from(value: Layout) -> Self {
         Rect {
             size: value.size,
-            top_left: value.top_left,
+            top_left: value.origin,
         }
     }
 }
 
+/// Stable identity of a widget across frames, independent of where it is drawn.
+/// IDs are hashed from a parent ID (see `UIContext::push_id`) and a source value.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct WidgetId(u64);
+
+impl WidgetId {
+    /// The root of every ID stack
+    pub const ROOT: WidgetId = WidgetId(0);
+
+    /// Creates an explicit ID from any hashable value, ignoring the ID stack.
+    pub fn new<H: Hash + ?Sized>(source: &H) -> Self {
+        Self::ROOT.with(source)
+    }
+
+    /// Creates an explicit ID from a label, ignoring the ID stack.
+    /// See `UIContext::id_from_label` for an ID relative to the current stack.
+    pub fn from_label(label: &str) -> Self {
+        Self::new(label)
+    }
+
+    /// Derives a child ID from this ID and `source`
+    pub fn with<H: Hash + ?Sized>(self, source: &H) -> Self {
+        let mut hasher = DefaultHasher::new();
+        self.0.hash(&mut hasher);
+        source.hash(&mut hasher);
+        WidgetId(hasher.finish())
+    }
+}
+
+/// An entry in the ID stack.
+/// Widgets without an explicit ID are numbered in order of creation inside it.
+#[derive(Debug, Clone, Copy)]
+struct IdScope {
+    id: WidgetId,
+    next_auto: u32,
+}
+
+impl IdScope {
+    fn new(id: WidgetId) -> Self {
+        Self { id, next_auto: 0 }
+    }
+
+    fn next_auto_id(&mut self) -> WidgetId {
+        let id = self.id.with(&self.next_auto);
+        self.next_auto += 1;
+        id
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct DrawData {
     pub rect: Rect,
@@ -168,6 +752,30 @@ pub enum DrawCommand {
     DrawRect {
         draw_data: DrawData,
     },
+    /// Clip the following draws to `rect` until the matching `PopClip`.
+    /// The following draws are in a space scrolled by `offset`: subtract it (and the offsets
+    /// of any enclosing clips) from their positions. `rect` itself is not shifted by `offset`.
+    PushClip {
+        rect: Rect,
+        offset: Vec2,
+    },
+    PopClip,
+}
+
+/// Draw commands are grouped into layers, drawn bottom to top.
+/// Widgets on a higher layer take hover from those under them.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Default)]
+pub enum Layer {
+    Background,
+    #[default]
+    Default,
+    Popup,
+    Tooltip,
+    Debug,
+}
+
+impl Layer {
+    const COUNT: usize = 5;
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
@@ -176,124 +784,799 @@ pub enum LayoutDirection {
     Horizontal,
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct Layout {
-    pub direction: LayoutDirection,
-    pub spacing: u32,
-    pub top_left: Vec2,
-    pub size: Vec2,
-}
+impl LayoutDirection {
+    /// The component of `v` along this direction
+    fn main(self, v: Vec2) -> u32 {
+        match self {
+            LayoutDirection::Vertical => v.y,
+            LayoutDirection::Horizontal => v.x,
+        }
+    }
 
-impl Layout {
-    pub fn new(direction: LayoutDirection, spacing: u32, top_left: Vec2, size: Vec2) -> Self {
-        Layout {
-            direction,
-            spacing,
-            top_left,
-            size,
+    /// The component of `v` across this direction
+    fn cross(self, v: Vec2) -> u32 {
+        match self {
+            LayoutDirection::Vertical => v.x,
+            LayoutDirection::Horizontal => v.y,
         }
     }
 
-    pub fn recompute(&mut self, size: Vec2) {
-        match self.direction {
-            LayoutDirection::Vertical => {
-                // add spacing if not first element
-                if self.size.y != 0 {
-                    self.size.y = self.size.y.saturating_add(self.spacing);
-                }
-                self.size.y = self.size.y.saturating_add(size.y);
-                self.size.x = self.size.x.max(size.x);
-                self.top_left.y = self
-                    .top_left
-                    .y
-                    .saturating_add(size.y.saturating_add(self.spacing));
-            }
-            LayoutDirection::Horizontal => {
-                if self.size.x != 0 {
-                    self.size.x = self.size.x.saturating_add(self.spacing);
-                }
-                self.size.x = self.size.x.saturating_add(size.x);
-                self.size.y = self.size.y.max(size.y);
-                self.top_left.x = self
-                    .top_left
-                    .x
-                    .saturating_add(size.x.saturating_add(self.spacing));
-            }
+    /// A vector from its components along and across this direction
+    fn vec(self, main: u32, cross: u32) -> Vec2 {
+        match self {
+            LayoutDirection::Vertical => Vec2::new(cross, main),
+            LayoutDirection::Horizontal => Vec2::new(main, cross),
         }
     }
 }
 
-/// Tell me how big your text is
-pub trait FontInformation {
-    fn compute_text_size(&self, text: &str, scale: f32) -> Vec2;
+/// Where children sit across a layout's direction
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Align {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// Children are widened to fill the layout
+    Stretch,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-/// Hint about what a draw command is for
-pub enum UIDrawRole {
-    Text,
-    ButtonText,
-    ButtonBackground,
-    SliderRect,
-    SliderKnob,
-    CheckboxBox,
-    CheckboxCheck,
-    LayoutBackground,
+/// How children are spread along a layout's direction
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Justify {
+    #[default]
+    Start,
+    Center,
+    End,
+    /// The first and last children touch the edges, with the space left between the others
+    SpaceBetween,
 }
 
-/// Tiny wrapper for an assumed-space-separated list of classes/tags.
-/// Allows for unique styling of UI elements (e.g. "cool-btn text-red").
-/// Fast enough if not used excessively.
-#[derive(Copy, Clone, Debug)]
-pub struct ClassList {
-    pub classes: &'static str,
+/// How much of a layout a child takes along the layout's direction, see `UIContext::set_next_length`.
+/// Fractions and fills need a layout with a known size, such as a fixed `width` or `height`,
+/// and otherwise leave the child its own size.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Length {
+    /// Exactly this many pixels, margin excluded
+    Fixed(u32),
+    /// This fraction of the layout's space inside its padding, margin included
+    Fraction(f32),
+    /// A share of the space the other children leave, by weight against the other fills.
+    /// Fills shrink away to nothing when the other children take all the space.
+    Fill(f32),
 }
 
-impl ClassList {
-    pub fn new(tags: &'static str) -> Self {
-        Self { classes: tags }
-    }
+/// How a layout places its children, see `UIContext::set_next_layout_style`.
+/// Alignment and justification need the space the layout's children took, which is measured
+/// over a frame: like hover, they settle the frame after the children change.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct LayoutStyle {
+    /// Space between the edges of the layout and its children, inside its background
+    pub padding: Insets,
+    pub align: Align,
+    pub justify: Justify,
+    /// The layout is at least this big, padding included, with children aligned and justified inside
+    pub min_size: Vec2,
+    /// Fixed width and height, padding included, instead of growing with the children.
+    /// Children that don't fit overflow.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
 
-    /// Returns true if these tags contain the given tag, by separating on whitespace.
-    pub fn has(&self, tag: &'static str) -> bool {
-        self.classes.split_whitespace().any(|t| t.eq(tag))
+impl LayoutStyle {
+    /// `size` with the fixed width and height applied
+    fn fixed(&self, size: Vec2) -> Vec2 {
+        Vec2::new(self.width.unwrap_or(size.x), self.height.unwrap_or(size.y))
     }
 }
 
-impl PartialEq for ClassList {
-    fn eq(&self, other: &Self) -> bool {
-        self.classes.eq(other.classes)
-    }
+/// How the next widget or layout sits in its parent, see `UIContext::set_next_margin`,
+/// `UIContext::set_next_length` and `UIContext::set_next_span`
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ChildStyle {
+    pub margin: Insets,
+    pub length: Option<Length>,
+    /// Grid columns the child takes, one if zero
+    pub span: usize,
 }
 
-#[derive(Debug, Copy, Clone)]
-/// Persistent UI state object
-pub struct UIState {
-    active_rect: Option<Rect>,
-    last_mouse_position: Vec2,
-    active_drag_amt: f32,
-    focused: Option<Rect>,
+/// How wide a grid column is, see `UIContext::grid`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GridColumn {
+    /// As wide as its widest cell, as of last frame or so far this frame.
+    /// Cells spanning several columns don't widen them.
+    Auto,
+    /// Exactly this many pixels
+    Fixed(u32),
+    /// This fraction of the grid's width inside its padding, less the spacing between columns.
+    /// Needs a grid with a known width, like `Length::Fraction`, and is otherwise `Auto`.
+    Fraction(f32),
 }
 
-impl Default for UIState {
-    fn default() -> Self {
-        Self::new()
-    }
+/// The column widths and row heights a grid measured
+#[derive(Debug, Clone, Default, PartialEq)]
+struct GridMeasure {
+    widths: Vec<u32>,
+    rows: Vec<u32>,
 }
 
-impl UIState {
-    pub fn new() -> Self {
-        Self {
-            active_rect: None,
-            last_mouse_position: Vec2::zero(),
+/// A layout's grid, placing children in cells left to right and then top to bottom
+#[derive(Debug, Clone)]
+struct Grid {
+    columns: Vec<GridColumn>,
+    row_spacing: u32,
+    /// Width fractional columns share
+    space: u32,
+    last: GridMeasure,
+    measure: GridMeasure,
+    /// Where the next cell goes, if it fits in the row
+    column: usize,
+    row: usize,
+}
+
+impl Grid {
+    fn new(columns: Vec<GridColumn>, spacing: Vec2, inner_width: u32, last: GridMeasure) -> Self {
+        let gaps = spacing.x * (columns.len() as u32 - 1);
+        Grid {
+            measure: GridMeasure {
+                widths: vec![0; columns.len()],
+                rows: vec![],
+            },
+            columns,
+            row_spacing: spacing.y,
+            space: inner_width.saturating_sub(gaps),
+            last,
+            column: 0,
+            row: 0,
+        }
+    }
+
+    /// The column and row of a cell `span` columns wide, the first of the next row if it
+    /// doesn't fit in this one, and its span within the grid
+    fn cell(&self, span: usize) -> (usize, usize, usize) {
+        let span = span.clamp(1, self.columns.len());
+        if self.column > 0 && self.column + span > self.columns.len() {
+            (0, self.row + 1, span)
+        } else {
+            (self.column, self.row, span)
+        }
+    }
+
+    /// Width of `column`, sized by `widths` if it's automatic
+    fn width(&self, column: usize, widths: &[u32]) -> u32 {
+        match self.columns[column] {
+            GridColumn::Fixed(width) => width,
+            GridColumn::Fraction(fraction) if self.space > 0 => {
+                (self.space as f32 * fraction) as u32
+            }
+            _ => widths[column],
+        }
+    }
+
+    /// `column` is as wide as its policy says, rather than as its cells
+    fn sized(&self, column: usize) -> bool {
+        match self.columns[column] {
+            GridColumn::Auto => false,
+            GridColumn::Fixed(_) => true,
+            GridColumn::Fraction(_) => self.space > 0,
+        }
+    }
+
+    /// Width of `column` as cells are placed. Automatic columns are as wide as they were last
+    /// frame or are so far, others never widen for their cells, which overflow.
+    fn placed_width(&self, column: usize) -> u32 {
+        let width = self.width(column, &self.measure.widths);
+        if self.sized(column) {
+            return width;
+        }
+        let last = self.last.widths.get(column).copied().unwrap_or(0);
+        width.max(last)
+    }
+
+    /// Height of `row` as cells are placed, as tall as it was last frame or is so far
+    fn placed_height(&self, row: usize) -> u32 {
+        let last = self.last.rows.get(row).copied().unwrap_or(0);
+        self.measure.rows.get(row).copied().unwrap_or(0).max(last)
+    }
+
+    /// Where the cell at `column` and `row` is from the grid's top left inside its padding
+    fn offset(&self, column: usize, row: usize, column_spacing: u32) -> Vec2 {
+        let x = (0..column)
+            .map(|column| self.placed_width(column) + column_spacing)
+            .sum();
+        let y = (0..row)
+            .map(|row| self.placed_height(row) + self.row_spacing)
+            .sum();
+        Vec2::new(x, y)
+    }
+}
+
+/// A layout's rows, placing children left to right until the next would pass the layout's
+/// width, then starting a row under them
+#[derive(Debug, Copy, Clone)]
+struct Flow {
+    /// Width children wrap at, padding excluded
+    width: u32,
+    row_spacing: u32,
+    /// Top of the current row and where its next child goes, from the top left inside the padding
+    row_top: u32,
+    row_end: u32,
+    /// Height of the tallest child in the current row
+    row_height: u32,
+    /// Width of the widest row
+    widest: u32,
+}
+
+impl Flow {
+    fn new(width: u32, row_spacing: u32) -> Self {
+        Flow {
+            width,
+            row_spacing,
+            row_top: 0,
+            row_end: 0,
+            row_height: 0,
+            widest: 0,
+        }
+    }
+
+    /// Where a child `width` wide goes from the top left inside the padding, starting a row if it
+    /// doesn't fit in this one
+    fn offset(&self, width: u32) -> Vec2 {
+        if self.row_end > 0 && self.row_end + width > self.width {
+            Vec2::new(0, self.row_top + self.row_height + self.row_spacing)
+        } else {
+            Vec2::new(self.row_end, self.row_top)
+        }
+    }
+}
+
+/// How a layout places its children
+#[derive(Debug, Clone, Default)]
+enum Arrangement {
+    /// One after another along the layout's direction
+    #[default]
+    Stack,
+    Grid(Grid),
+    Flow(Flow),
+}
+
+/// Space a widget or layout took, and for layouts the space their children took, margins included
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+struct Measure {
+    /// Size of the widget or layout itself, padding included
+    size: Vec2,
+    /// Size of a layout before its parent stretched it
+    natural: Vec2,
+    children: usize,
+    /// Total size along the layout's direction, without spacing
+    main: u32,
+    /// Size of the largest child across the layout's direction
+    cross: u32,
+    /// Width of the widest label on the left of a widget, see `UIContext::label_column`
+    label_width: u32,
+    /// Total size along the layout's direction of the children filling it, and their weights
+    fill_main: u32,
+    fill_weight: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pub direction: LayoutDirection,
+    pub spacing: u32,
+    /// Where the next child goes, before its margin and alignment
+    pub top_left: Vec2,
+    /// Space taken so far, padding included
+    pub size: Vec2,
+    pub style: LayoutStyle,
+    /// Space taken without being stretched by its parent, which it takes in its parent
+    natural: Vec2,
+    /// Top left of the layout, outside its padding
+    origin: Vec2,
+    /// The layout is at least this big: its style's `min_size`, or more when stretched by its parent
+    min_size: Vec2,
+    /// Space children are aligned in, padding excluded
+    inner: Vec2,
+    /// Extra space between children from justification
+    gap: u32,
+    /// Space along the layout's direction left by the children not filling it, and the
+    /// total weight of the fills, as of last frame
+    fill_space: u32,
+    fill_weight: f32,
+    /// Identifies the layout's measure across frames
+    id: WidgetId,
+    measure: Measure,
+    arrangement: Arrangement,
+}
+
+impl Layout {
+    pub fn new(direction: LayoutDirection, spacing: u32, top_left: Vec2, size: Vec2) -> Self {
+        Layout {
+            direction,
+            spacing,
+            top_left,
+            size,
+            style: LayoutStyle::default(),
+            natural: size,
+            origin: top_left,
+            min_size: Vec2::zero(),
+            inner: Vec2::zero(),
+            gap: 0,
+            fill_space: 0,
+            fill_weight: 0.0,
+            id: WidgetId::ROOT,
+            measure: Measure::default(),
+            arrangement: Arrangement::Stack,
+        }
+    }
+
+    /// Layout `id` at `top_left` at least `min_size` big, arranging its children as they were
+    /// measured last frame, if it was drawn.
+    fn arranged(
+        id: WidgetId,
+        direction: LayoutDirection,
+        spacing: u32,
+        top_left: Vec2,
+        style: LayoutStyle,
+        min_size: Vec2,
+        last: Option<Measure>,
+    ) -> Self {
+        let d = direction;
+        let padding = style.padding.size();
+        let min_size = style.fixed(Vec2::max_cmp(style.min_size, min_size));
+        let last = last.unwrap_or_default();
+        // a layout that isn't fixed across is as wide as its widest child was
+        let fixed_cross = d.cross(style.fixed(Vec2::zero())) > 0;
+        let inner = Vec2::sub(Vec2::max_cmp(min_size, padding), padding);
+        let inner = if fixed_cross {
+            inner
+        } else {
+            d.vec(d.main(inner), d.cross(inner).max(last.cross))
+        };
+
+        // spread last frame's children over the free space
+        let spacing_total = spacing * last.children.saturating_sub(1) as u32;
+        let fill_space = d
+            .main(inner)
+            .saturating_sub(last.main - last.fill_main + spacing_total);
+        let free = d.main(inner).saturating_sub(last.main + spacing_total);
+        let (offset, gap) = match style.justify {
+            Justify::Start => (0, 0),
+            Justify::Center => (free / 2, 0),
+            Justify::End => (free, 0),
+            Justify::SpaceBetween if last.children > 1 => (0, free / (last.children as u32 - 1)),
+            Justify::SpaceBetween => (0, 0),
+        };
+
+        Layout {
+            direction,
+            spacing,
+            top_left: Vec2::add(Vec2::add(top_left, style.padding.start()), d.vec(offset, 0)),
+            size: style.fixed(Vec2::max_cmp(min_size, padding)),
+            natural: style.fixed(Vec2::max_cmp(style.min_size, padding)),
+            style,
+            origin: top_left,
+            min_size,
+            inner,
+            gap,
+            fill_space,
+            fill_weight: last.fill_weight,
+            id,
+            measure: Measure::default(),
+            arrangement: Arrangement::Stack,
+        }
+    }
+
+    /// This layout placing its children by `arrangement` instead, from its top left inside its padding
+    fn with_arrangement(self, arrangement: Arrangement) -> Self {
+        Layout {
+            top_left: Vec2::add(self.origin, self.style.padding.start()),
+            arrangement,
+            ..self
+        }
+    }
+
+    /// Where a child of `size` goes, after its margin, sized along the layout by its length and
+    /// aligned across it. Stretched children are widened to fill the layout.
+    pub fn place(&self, size: Vec2, child: &ChildStyle) -> Rect {
+        match &self.arrangement {
+            Arrangement::Stack => {}
+            Arrangement::Grid(grid) => return self.place_in_cell(grid, size, child).0,
+            Arrangement::Flow(flow) => return self.place_in_flow(flow, size, child).0,
+        }
+        let d = self.direction;
+        let margin = child.margin;
+        let size = d.vec(
+            self.main_length(d.main(size), margin, child.length),
+            d.cross(size),
+        );
+        let outer = d.cross(size) + d.cross(margin.size());
+        let free = d.cross(self.inner).saturating_sub(outer);
+        let (offset, cross) = match self.style.align {
+            Align::Start => (0, d.cross(size)),
+            Align::Center => (free / 2, d.cross(size)),
+            Align::End => (free, d.cross(size)),
+            Align::Stretch => (0, d.cross(size) + free),
+        };
+        let top_left = Vec2::add(Vec2::add(self.top_left, margin.start()), d.vec(0, offset));
+        Rect {
+            top_left,
+            size: d.vec(d.main(size), cross),
+        }
+    }
+
+    /// Whether the child's length sizes it here rather than leaving it its own size, as fractions
+    /// and fills do without known space to share. Child layouts are fixed to lengths that do.
+    fn sizes(&self, child: &ChildStyle) -> bool {
+        match (child.length, &self.arrangement) {
+            (None, _) => false,
+            (Some(Length::Fixed(_)), _) => true,
+            (Some(_), Arrangement::Stack) => self.direction.main(self.inner) > 0,
+            (Some(_), Arrangement::Grid(grid)) => {
+                let (column, _, span) = grid.cell(child.span);
+                (column..column + span).all(|column| grid.sized(column))
+            }
+            (Some(_), Arrangement::Flow(_)) => true,
+        }
+    }
+
+    /// Size along the layout's direction of a child `natural` big with `margin` around it
+    fn main_length(&self, natural: u32, margin: Insets, length: Option<Length>) -> u32 {
+        let margin = self.direction.main(margin.size());
+        let space = self.direction.main(self.inner);
+        match length {
+            Some(Length::Fixed(length)) => length,
+            Some(Length::Fraction(fraction)) if space > 0 => {
+                ((space as f32 * fraction) as u32).saturating_sub(margin)
+            }
+            Some(Length::Fill(weight)) if space > 0 => {
+                let share = weight / self.fill_weight.max(weight);
+                ((self.fill_space as f32 * share) as u32).saturating_sub(margin)
+            }
+            _ => natural,
+        }
+    }
+
+    /// Where a child of `size` goes in the cell after the last, aligned in its row, with the
+    /// cell's column and row and the child's size in it, margin included
+    fn place_in_cell(
+        &self,
+        grid: &Grid,
+        size: Vec2,
+        child: &ChildStyle,
+    ) -> (Rect, usize, usize, Vec2) {
+        let (column, row, span) = grid.cell(child.span);
+        let cell_width = (column..column + span)
+            .map(|column| grid.placed_width(column))
+            .sum::<u32>()
+            + self.spacing * (span as u32 - 1);
+
+        // lengths are taken from the cell's width
+        let margin = child.margin;
+        let width = match child.length {
+            Some(Length::Fixed(width)) => width,
+            Some(Length::Fraction(fraction)) => {
+                ((cell_width as f32 * fraction) as u32).saturating_sub(margin.size().x)
+            }
+            Some(Length::Fill(_)) => cell_width.saturating_sub(margin.size().x),
+            None => size.x,
+        };
+        let outer = Vec2::add(Vec2::new(width, size.y), margin.size());
+        let free = grid.placed_height(row).saturating_sub(outer.y);
+        let (offset, height) = match self.style.align {
+            Align::Start => (0, size.y),
+            Align::Center => (free / 2, size.y),
+            Align::End => (free, size.y),
+            Align::Stretch => (0, size.y + free),
+        };
+        let cell = Vec2::add(
+            Vec2::add(self.origin, self.style.padding.start()),
+            grid.offset(column, row, self.spacing),
+        );
+        let rect = Rect {
+            top_left: Vec2::add(Vec2::add(cell, Vec2::new(0, offset)), margin.start()),
+            size: Vec2::new(width, height),
+        };
+        (rect, column, row, outer)
+    }
+
+    /// Sizes the layout around children taking `content`, padding included
+    fn fit(&mut self, content: Vec2) {
+        self.natural = self
+            .style
+            .fixed(Vec2::max_cmp(self.style.min_size, content));
+        self.size = self.style.fixed(Vec2::max_cmp(self.min_size, content));
+    }
+
+    /// Moves past a child of `size`
+    pub fn recompute(&mut self, size: Vec2) {
+        self.recompute_child(size, &ChildStyle::default());
+    }
+
+    /// Moves past a child of `size`, after its margin and sized along the layout by its length.
+    /// `size` is the child's own, before the layout stretched it.
+    pub fn recompute_child(&mut self, size: Vec2, child: &ChildStyle) {
+        match std::mem::take(&mut self.arrangement) {
+            Arrangement::Stack => {}
+            Arrangement::Grid(mut grid) => {
+                self.recompute_cell(&mut grid, size, child);
+                self.arrangement = Arrangement::Grid(grid);
+                return;
+            }
+            Arrangement::Flow(mut flow) => {
+                self.recompute_flow(&mut flow, size, child);
+                self.arrangement = Arrangement::Flow(flow);
+                return;
+            }
+        }
+        let d = self.direction;
+        let (margin, length) = (child.margin, child.length);
+        let size = d.vec(
+            self.main_length(d.main(size), margin, length),
+            d.cross(size),
+        );
+        let outer = Vec2::add(size, margin.size());
+        let end = d.main(self.top_left) + d.main(outer);
+        self.measure.children += 1;
+        self.measure.main += d.main(outer);
+        self.measure.cross = self.measure.cross.max(d.cross(outer));
+        if let Some(Length::Fill(weight)) = length {
+            self.measure.fill_main += d.main(outer);
+            self.measure.fill_weight += weight;
+        }
+
+        let padding = self.style.padding;
+        let main = (end + d.main(Vec2::sub(padding.size(), padding.start())))
+            .saturating_sub(d.main(self.origin));
+        let cross = self.measure.cross + d.cross(padding.size());
+        self.fit(d.vec(main, cross));
+        self.top_left = Vec2::add(
+            self.top_left,
+            d.vec(d.main(outer) + self.spacing + self.gap, 0),
+        );
+    }
+
+    /// Moves past a child of `size` in the next cell of the grid
+    fn recompute_cell(&mut self, grid: &mut Grid, size: Vec2, child: &ChildStyle) {
+        let (_, column, row, outer) = self.place_in_cell(grid, size, child);
+        let span = grid.cell(child.span).2;
+        grid.column = column + span;
+        grid.row = row;
+        self.measure.children += 1;
+
+        // cells filling their columns count at their own width, not the width they were filled to
+        if span == 1 {
+            let width = match child.length {
+                Some(Length::Fraction(_) | Length::Fill(_)) => size.x + child.margin.size().x,
+                _ => outer.x,
+            };
+            let widths = &mut grid.measure.widths;
+            widths[column] = widths[column].max(width);
+        }
+        let rows = &mut grid.measure.rows;
+        if rows.len() <= row {
+            rows.resize(row + 1, 0);
+        }
+        rows[row] = rows[row].max(outer.y);
+
+        let columns = grid.columns.len();
+        let width = (0..columns)
+            .map(|column| grid.width(column, &grid.measure.widths))
+            .sum::<u32>()
+            + self.spacing * (columns as u32 - 1);
+        let rows = &grid.measure.rows;
+        let height = rows.iter().sum::<u32>() + grid.row_spacing * (rows.len() as u32 - 1);
+        let size = Vec2::add(Vec2::new(width, height), self.style.padding.size());
+        self.fit(size);
+
+        let (column, row, _) = grid.cell(1);
+        self.top_left = Vec2::add(
+            Vec2::add(self.origin, self.style.padding.start()),
+            grid.offset(column, row, self.spacing),
+        );
+    }
+
+    /// Where a child of `size` goes after the last in `flow`, with its offset from the top left
+    /// inside the padding and its size, margin included
+    fn place_in_flow(&self, flow: &Flow, size: Vec2, child: &ChildStyle) -> (Rect, Vec2, Vec2) {
+        // fills take the rest of the row
+        let margin = child.margin;
+        let width = match child.length {
+            Some(Length::Fixed(width)) => width,
+            Some(Length::Fraction(fraction)) => {
+                ((flow.width as f32 * fraction) as u32).saturating_sub(margin.size().x)
+            }
+            Some(Length::Fill(_)) => flow.width.saturating_sub(flow.row_end + margin.size().x),
+            None => size.x,
+        };
+        let outer = Vec2::add(Vec2::new(width, size.y), margin.size());
+        let offset = flow.offset(outer.x);
+        let rect = Rect {
+            top_left: Vec2::add(
+                Vec2::add(Vec2::add(self.origin, self.style.padding.start()), offset),
+                margin.start(),
+            ),
+            size: Vec2::new(width, size.y),
+        };
+        (rect, offset, outer)
+    }
+
+    /// Moves past a child of `size` in `flow`, starting a row if it doesn't fit in this one
+    fn recompute_flow(&mut self, flow: &mut Flow, size: Vec2, child: &ChildStyle) {
+        let (_, offset, outer) = self.place_in_flow(flow, size, child);
+        if offset.y != flow.row_top {
+            flow.row_top = offset.y;
+            flow.row_height = 0;
+        }
+        flow.row_end = offset.x + outer.x + self.spacing;
+        flow.row_height = flow.row_height.max(outer.y);
+        flow.widest = flow.widest.max(offset.x + outer.x);
+        self.measure.children += 1;
+
+        let size = Vec2::new(flow.widest, flow.row_top + flow.row_height);
+        let size = Vec2::add(size, self.style.padding.size());
+        self.fit(size);
+        self.top_left = Vec2::add(
+            Vec2::add(self.origin, self.style.padding.start()),
+            Vec2::new(flow.row_end, flow.row_top),
+        );
+    }
+}
+
+/// Tell me how big your text is
+pub trait FontInformation {
+    fn compute_text_size(&self, text: &str, scale: f32) -> Vec2;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Hint about what a draw command is for
+pub enum UIDrawRole {
+    Text,
+    ButtonText,
+    ButtonBackground,
+    SliderRect,
+    SliderKnob,
+    CheckboxBox,
+    CheckboxCheck,
+    LayoutBackground,
+    TextInputBackground,
+    TextInputText,
+    TextInputCaret,
+    TextInputSelection,
+    ScrollbarTrack,
+    ScrollbarThumb,
+    RadioOuter,
+    RadioDot,
+    ComboBoxBackground,
+    ComboBoxText,
+    ComboBoxArrow,
+    PopupBackground,
+    /// An entry of an open popup list, flagged `HOVER` while highlighted and `SELECTED` for the current value
+    PopupItem,
+    PopupItemText,
+    /// Accelerator text of a button or menu item, see `UIContext::set_next_shortcut`
+    ShortcutText,
+}
+
+/// Tiny wrapper for an assumed-space-separated list of classes/tags.
+/// Allows for unique styling of UI elements (e.g. "cool-btn text-red").
+/// Fast enough if not used excessively.
+#[derive(Copy, Clone, Debug)]
+pub struct ClassList {
+    pub classes: &'static str,
+}
+
+impl ClassList {
+    pub fn new(tags: &'static str) -> Self {
+        Self { classes: tags }
+    }
+
+    /// Returns true if these tags contain the given tag, by separating on whitespace.
+    pub fn has(&self, tag: &'static str) -> bool {
+        self.classes.split_whitespace().any(|t| t.eq(tag))
+    }
+}
+
+impl PartialEq for ClassList {
+    fn eq(&self, other: &Self) -> bool {
+        self.classes.eq(other.classes)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Persistent UI state object
+pub struct UIState {
+    active: Option<WidgetId>,
+    last_mouse_position: Vec2,
+    active_drag_amt: f32,
+    focused: Option<WidgetId>,
+    focused_rect: Option<Rect>,
+    /// Focus changed in the last frame
+    focus_moved: bool,
+    /// The focused widget at the start of the last frame
+    last_focused: Option<WidgetId>,
+    /// Fractions of wheel notches not yet applied to `wheel_target`
+    wheel_remainder: f32,
+    wheel_target: Option<WidgetId>,
+    /// A radio that arrow keys moved focus to, which selects itself when next drawn
+    radio_selected: Option<WidgetId>,
+    /// The combo box whose popup is open
+    open_popup: Option<WidgetId>,
+    popup_highlight: usize,
+    /// The widget whose context menu is open, and where on screen
+    context_menu: Option<(WidgetId, Vec2)>,
+    /// The topmost widget under the mouse last frame, the only one that reports hover
+    hovered: Option<WidgetId>,
+    /// Focus scopes open as of last frame, in the order they opened
+    focus_scopes: Vec<FocusScope>,
+    /// The widget being dragged last frame
+    dragged: Option<WidgetId>,
+    /// The last widget clicked and when, for double clicks
+    last_click: Option<(WidgetId, f64)>,
+    /// The registration each shortcut triggers, resolved last frame
+    shortcut_winners: Vec<(Shortcut, WidgetId)>,
+    /// Shortcuts pressed last frame that nothing triggered for, because the registration that
+    /// won them wasn't drawn. They trigger for this frame's winner instead.
+    shortcuts_pending: Vec<Shortcut>,
+    /// What each widget and layout measured last frame
+    measures: HashMap<WidgetId, Measure>,
+    grids: HashMap<WidgetId, GridMeasure>,
+    /// Enter or Space was pressed and not yet released, see `UIInputState::apply_key_events`
+    activate_key_held: bool,
+}
+
+impl Default for UIState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UIState {
+    pub fn new() -> Self {
+        Self {
+            active: None,
+            last_mouse_position: Vec2::zero(),
             active_drag_amt: 0.0,
             focused: None,
+            focused_rect: None,
+            focus_moved: false,
+            last_focused: None,
+            wheel_remainder: 0.0,
+            wheel_target: None,
+            radio_selected: None,
+            open_popup: None,
+            popup_highlight: 0,
+            context_menu: None,
+            hovered: None,
+            focus_scopes: vec![],
+            dragged: None,
+            last_click: None,
+            shortcut_winners: vec![],
+            shortcuts_pending: vec![],
+            measures: HashMap::new(),
+            grids: HashMap::new(),
+            activate_key_held: false,
         }
     }
 
-    pub fn focused_rect(&self) -> Option<Rect> {
+    /// The ID of the hovered widget, if any
+    pub fn hovered_id(&self) -> Option<WidgetId> {
+        self.hovered
+    }
+
+    /// The ID of the focused widget, if any
+    pub fn focused_id(&self) -> Option<WidgetId> {
         self.focused
     }
+
+    /// Where the focused widget was last drawn
+    pub fn focused_rect(&self) -> Option<Rect> {
+        self.focused_rect
+    }
+
+    /// Focuses `id` from outside a frame, e.g. when opening a dialog
+    pub fn set_focused(&mut self, id: Option<WidgetId>) {
+        self.focused = id;
+    }
 }
 
 /// Result of a computation of the UI
@@ -309,11 +1592,42 @@ pub struct UIInputState {
 
     pub activate_button: ButtonState,
     pub focus_next_button: ButtonState,
+    /// Moves focus backwards, usually shift+tab
+    pub focus_prev_button: ButtonState,
+    /// Closes popups, or clears focus if no popup is open. Usually escape
+    pub cancel_button: ButtonState,
+    /// Usually the right mouse button, opens context menus
+    pub secondary_button: ButtonState,
+    pub middle_button: ButtonState,
 
     pub move_left: ButtonState,
     pub move_right: ButtonState,
     pub move_up: ButtonState,
     pub move_down: ButtonState,
+
+    /// Text editing keys. `Down` is treated as a press, so key repeats may be reported as `Down`.
+    pub backspace_button: ButtonState,
+    pub delete_button: ButtonState,
+    pub home_button: ButtonState,
+    pub end_button: ButtonState,
+    pub page_up_button: ButtonState,
+    pub page_down_button: ButtonState,
+
+    /// Modifier keys held this frame. Shift extends text selections.
+    pub modifiers: Modifiers,
+    /// Keys pressed and released this frame, in order. See `apply_key_events` to drive the
+    /// named buttons above from them.
+    pub key_events: Vec<KeyEvent>,
+    /// Characters typed this frame, in order
+    pub typed_chars: Vec<char>,
+
+    /// Mouse wheel movement this frame in notches, fractional for smooth wheels and touchpads.
+    /// Positive values scroll towards the start of the content: up, or left for `wheel_x`.
+    pub wheel_x: f32,
+    pub wheel_y: f32,
+
+    /// Seconds since any fixed point, such as the start of the program. Used to detect double clicks.
+    pub time: f64,
 }
 
 impl Default for UIInputState {
@@ -323,19 +1637,143 @@ impl Default for UIInputState {
             mouse_position: Vec2::zero(),
             activate_button: ButtonState::Up,
             focus_next_button: ButtonState::Up,
+            focus_prev_button: ButtonState::Up,
+            cancel_button: ButtonState::Up,
+            secondary_button: ButtonState::Up,
+            middle_button: ButtonState::Up,
             move_left: ButtonState::Up,
             move_right: ButtonState::Up,
             move_up: ButtonState::Up,
             move_down: ButtonState::Up,
+            backspace_button: ButtonState::Up,
+            delete_button: ButtonState::Up,
+            home_button: ButtonState::Up,
+            end_button: ButtonState::Up,
+            page_up_button: ButtonState::Up,
+            page_down_button: ButtonState::Up,
+            modifiers: Modifiers::NONE,
+            key_events: vec![],
+            typed_chars: vec![],
+            wheel_x: 0.0,
+            wheel_y: 0.0,
+            time: 0.0,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-/// Data about what happened to draw a checkbox
-pub struct CheckboxResult { 
+impl UIInputState {
+    /// Sets the named buttons from `key_events`, for backends that only report key events.
+    /// Presses and repeats set `Down` and releases `Released`, the last event of a key winning.
+    /// Tab moves focus backwards with shift held.
+    /// Enter and Space activate the focused widget, held between their press and release as
+    /// remembered by last frame's `state`.
+    pub fn apply_key_events(&mut self, state: &UIState) {
+        if state.activate_key_held {
+            self.activate_button = ButtonState::Held;
+        }
+        for idx in 0..self.key_events.len() {
+            let event = self.key_events[idx];
+            let button_state = if event.pressed {
+                ButtonState::Down
+            } else {
+                ButtonState::Released
+            };
+            let button = match event.key {
+                // repeats keep activating what the press did
+                Key::Enter | Key::Space if event.repeat => {
+                    self.activate_button = ButtonState::Held;
+                    continue;
+                }
+                Key::Enter | Key::Space => &mut self.activate_button,
+                Key::Tab if event.modifiers.shift => &mut self.focus_prev_button,
+                Key::Tab => &mut self.focus_next_button,
+                Key::Escape => &mut self.cancel_button,
+                Key::Left => &mut self.move_left,
+                Key::Right => &mut self.move_right,
+                Key::Up => &mut self.move_up,
+                Key::Down => &mut self.move_down,
+                Key::Backspace => &mut self.backspace_button,
+                Key::Delete => &mut self.delete_button,
+                Key::Home => &mut self.home_button,
+                Key::End => &mut self.end_button,
+                Key::PageUp => &mut self.page_up_button,
+                Key::PageDown => &mut self.page_down_button,
+                _ => continue,
+            };
+            *button = button_state;
+        }
+    }
+
+    /// Enter or Space went down this frame, activating the focused widget rather than the mouse
+    fn activation_key_pressed(&self) -> bool {
+        self.key_events.iter().any(|event| {
+            event.pressed && !event.repeat && matches!(event.key, Key::Enter | Key::Space)
+        })
+    }
+}
+
+/// What happened to a widget this frame, returned by every widget
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Response {
+    pub id: WidgetId,
+    /// Where the widget was drawn, in the current (possibly scrolled) space
     pub rect: Rect,
-    pub interacted: bool,
+    pub hovered: bool,
+    /// The activate button went down over the widget this frame
+    pub pressed: bool,
+    /// Activated this frame, by the mouse or keyboard
+    pub clicked: bool,
+    /// Clicked a second time within `DOUBLE_CLICK_TIME` of the last click
+    pub double_clicked: bool,
+    /// The secondary button was released over the widget this frame
+    pub secondary_clicked: bool,
+    /// The middle button was released over the widget this frame
+    pub middle_clicked: bool,
+    /// The first frame the activate button is held after pressing the widget
+    pub drag_started: bool,
+    /// Pressed and the activate button still held
+    pub dragging: bool,
+    /// The frame a drag ended
+    pub drag_stopped: bool,
+    pub focus_gained: bool,
+    pub focus_lost: bool,
+    /// The widget changed the value it edits
+    pub changed: bool,
+}
+
+/// Seconds between the clicks of a double click
+pub const DOUBLE_CLICK_TIME: f64 = 0.4;
+
+/// Gap between the edge of a text field and its text
+const TEXT_INPUT_INSET: u32 = 4;
+const TEXT_INPUT_CARET_WIDTH: u32 = 2;
+/// Thickness of scroll area scrollbars
+const SCROLLBAR_SIZE: u32 = 10;
+const SCROLLBAR_MIN_THUMB: u32 = 10;
+/// Vertical space around the text of a context menu item
+const MENU_ITEM_PADDING: u32 = 8;
+/// Gap between a label and its accelerator text
+const SHORTCUT_GAP: u32 = 16;
+/// Pixels scrolled per notch of the mouse wheel
+const WHEEL_SCROLL_STEP: f32 = 40.0;
+
+/// A clipped region of the screen whose contents are scrolled by `offset`
+#[derive(Debug, Clone, Copy)]
+struct ClipRegion {
+    /// Screen space
+    rect: Rect,
+    /// Total scroll of this region and its parents
+    offset: Vec2,
+}
+
+/// A subtree of widgets focus traversal can be confined to, see `UIContext::with_focus_scope`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct FocusScope {
+    id: WidgetId,
+    /// Traversal is confined to the scope's widgets while it is open
+    trap: bool,
+    /// Focus when the scope opened, restored when it closes
+    restore: Option<WidgetId>,
 }
 
 /// Transient draw context
@@ -344,15 +1782,76 @@ pub struct UIContext<'f> {
     font_info: &'f dyn FontInformation,
     input_state: UIInputState,
 
-    hover_rect: Option<Rect>,
+    /// Widgets under the mouse this frame and their layers, in draw order
+    hits: Vec<(WidgetId, Layer)>,
 
     command_buffer: VecDeque<DrawCommand>,
+    /// Commands of the layers not being drawn to
+    layer_buffers: [VecDeque<DrawCommand>; Layer::COUNT],
+    layer: Layer,
 
     layout_stack: Vec<Layout>,
+    id_stack: Vec<IdScope>,
+    /// What each widget and layout drawn this frame measured
+    measures: HashMap<WidgetId, Measure>,
+    grids: HashMap<WidgetId, GridMeasure>,
+    next_layout_style: Option<LayoutStyle>,
+    /// How the next child of the current layout sits in it
+    next_child: ChildStyle,
+    /// Columns and spacing of the next layout, if it's a grid
+    next_grid: Option<(Vec<GridColumn>, Vec2)>,
+    /// Width and row spacing of the next layout, if it's a flow
+    next_flow: Option<(u32, u32)>,
 
     next_class: Option<ClassList>,
-
-    focusables: Vec<Rect>,
+    /// Widgets are drawn flagged `DISABLED` and ignore input
+    disabled: bool,
+    next_id: Option<WidgetId>,
+
+    /// Widgets focus can move to, with where they were drawn and their innermost trapping focus scope
+    focusables: Vec<(WidgetId, Rect, Option<WidgetId>)>,
+    /// Where the focused widget was registered: clip stack depth and rect in that clip's space
+    focused_seen: Option<(usize, Rect)>,
+    focus_at_start: Option<WidgetId>,
+    /// Focus set from code this frame, applied in `end`. `Some(None)` clears focus
+    focus_request: Option<Option<WidgetId>>,
+    /// The ID most recently taken by a widget or layout
+    last_id: Option<WidgetId>,
+    /// Radios drawn this frame, with the address and type of the value their group edits
+    radios: Vec<((usize, TypeId), WidgetId)>,
+    /// Focus scopes the current widget is inside, innermost last
+    focus_scope_stack: Vec<FocusScope>,
+    /// Focus scopes drawn this frame
+    focus_scopes_drawn: Vec<FocusScope>,
+    /// Focus scopes the focused widget was drawn inside, outermost first
+    focused_scopes: Vec<WidgetId>,
+    /// Shortcuts registered this frame, with the focus scopes they were registered inside
+    shortcuts: Vec<(Shortcut, WidgetId, Vec<WidgetId>)>,
+    /// Shortcuts that triggered this frame
+    shortcuts_triggered: Vec<Shortcut>,
+    /// Shortcut for the next button or menu item
+    next_shortcut: Option<Shortcut>,
+
+    clip_stack: Vec<ClipRegion>,
+
+    wheel_consumed: bool,
+    /// A widget used `cancel_button` this frame, so it doesn't clear focus
+    cancel_consumed: bool,
+    /// Focus traversal wraps around at the ends
+    focus_wrap: bool,
+    /// Unused move keys jump focus to the nearest widget in their direction
+    directional_focus: bool,
+    /// Widgets used the horizontal and vertical move keys this frame
+    moves_consumed: (bool, bool),
+
+    /// The open popup was drawn this frame
+    popup_drawn: bool,
+    /// The open context menu was drawn this frame
+    context_menu_drawn: bool,
+    /// Width of the items of the context menu being drawn
+    menu_width: u32,
+    /// An item of the context menu being drawn was chosen
+    menu_item_chosen: bool,
 }
 
 impl<'f> UIContext<'f> {
@@ -367,16 +1866,49 @@ impl<'f> UIContext<'f> {
             Vec2::zero(),
             Vec2::zero(),
         )];
+        let focus_at_start = state.focused;
 
         Self {
             command_buffer: VecDeque::new(),
+            layer_buffers: Default::default(),
+            layer: Layer::Default,
             input_state,
-            hover_rect: None,
+            hits: vec![],
             state,
             font_info,
             layout_stack: initial_layout_stack,
+            id_stack: vec![IdScope::new(WidgetId::ROOT)],
+            measures: HashMap::new(),
+            grids: HashMap::new(),
+            next_layout_style: None,
+            next_child: ChildStyle::default(),
+            next_grid: None,
+            next_flow: None,
             next_class: None,
+            disabled: false,
+            next_id: None,
             focusables: vec![],
+            focused_seen: None,
+            focus_at_start,
+            focus_request: None,
+            last_id: None,
+            radios: vec![],
+            focus_scope_stack: vec![],
+            focus_scopes_drawn: vec![],
+            focused_scopes: vec![],
+            shortcuts: vec![],
+            shortcuts_triggered: vec![],
+            next_shortcut: None,
+            clip_stack: vec![],
+            wheel_consumed: false,
+            cancel_consumed: false,
+            focus_wrap: true,
+            directional_focus: false,
+            moves_consumed: (false, false),
+            popup_drawn: false,
+            context_menu_drawn: false,
+            menu_width: 0,
+            menu_item_chosen: false,
         }
     }
 
@@ -387,23 +1919,14 @@ impl<'f> UIContext<'f> {
         position: Vec2,
         spacing: u32,
     ) -> Self {
-        let initial_layout_stack = vec![Layout::new(
+        let mut ctx = Self::new(state, font_info, input_state);
+        ctx.layout_stack = vec![Layout::new(
             LayoutDirection::Horizontal,
             spacing,
             position,
             Vec2::zero(),
         )];
-
-        Self {
-            command_buffer: VecDeque::new(),
-            input_state,
-            hover_rect: None,
-            state,
-            font_info,
-            layout_stack: initial_layout_stack,
-            next_class: None,
-            focusables: vec![],
-        }
+        ctx
     }
 
     pub fn get_current_layout(&self) -> &Layout {
@@ -412,44 +1935,500 @@ impl<'f> UIContext<'f> {
             .expect("get layout: should always have a root layout")
     }
 
+    /// Moves the current layout past a child of `size`, with the margin, length and span set by
+    /// `set_next_margin`, `set_next_length` and `set_next_span`
     pub fn recompute_current_layout(&mut self, size: Vec2) {
+        let child = std::mem::take(&mut self.next_child);
         self.layout_stack
             .last_mut()
             .expect("compute layout: should always have a root layout")
-            .recompute(size);
+            .recompute_child(size, &child);
     }
 
-    pub fn register_focusable(&mut self, rect: Rect) -> bool {
-        self.focusables.push(rect);
-        self.state.focused.is_some_and(|r| r == rect)
+    /// Where a child of `size` goes in the current layout, after its margin, alignment and length.
+    /// For custom widgets: draw there, then pass `size` to `recompute_current_layout`, which
+    /// takes the same length again but leaves out the stretch, so the layout can shrink.
+    pub fn layout_child_rect(&self, size: Vec2) -> Rect {
+        self.get_current_layout().place(size, &self.next_child)
     }
 
-    /// Sets the classlist used for all draws to `class_list`.
-    /// Clear it using `clear_class_list` or see `with_class_list`.
-    pub fn set_class_list(&mut self, class_list: ClassList) {
-        self.next_class = Some(class_list);
+    /// The size widget or layout `id` was drawn at last frame, if it was drawn.
+    /// Layouts are measured with their padding, from the ID they took (see `set_next_id`).
+    pub fn measured_size(&self, id: WidgetId) -> Option<Vec2> {
+        self.state.measures.get(&id).map(|measure| measure.size)
     }
 
-    /// Clears the currently used class list for drawing.
-    /// Set it using `set_class_list` or see `with_class_list`.
-    pub fn clear_class_list(&mut self) {
-        self.next_class = None;
+    /// Remembers what `layout` measured for next frame
+    fn measure_layout(&mut self, layout: &Layout) {
+        self.measures.insert(
+            layout.id,
+            Measure {
+                size: layout.size,
+                natural: layout.natural,
+                ..layout.measure
+            },
+        );
+        if let Arrangement::Grid(grid) = &layout.arrangement {
+            self.grids.insert(layout.id, grid.measure.clone());
+        }
     }
 
-    /// Executes `func` providing this UI context and returning its result, with the `class_list` set for the duration of the call.
-    pub fn with_class_list<F, T>(&mut self, class_list: ClassList, func: F) -> T
-    where
-        F: FnOnce(&mut Self) -> T,
-    {
-        self.set_class_list(class_list);
-        let ret = func(self);
-        self.clear_class_list();
-        ret
+    /// Styles the next layout, see `LayoutStyle`
+    pub fn set_next_layout_style(&mut self, style: LayoutStyle) {
+        self.next_layout_style = Some(style);
     }
 
-    /// Returns the index into the command buffer of this draw
+    /// Puts `margin` around the next widget or layout drawn in the current layout
+    pub fn set_next_margin(&mut self, margin: Insets) {
+        self.next_child.margin = margin;
+    }
+
+    /// Sizes the next widget or layout drawn in the current layout along its direction, see `Length`.
+    /// Widgets given a size, like sliders, and layouts take the length; others only take up the space.
+    pub fn set_next_length(&mut self, length: Length) {
+        self.next_child.length = Some(length);
+    }
+
+    /// Spans the next widget or layout drawn in the current grid across `columns` columns.
+    /// A cell that doesn't fit in the rest of its row starts the next.
+    pub fn set_next_span(&mut self, columns: usize) {
+        self.next_child.span = columns;
+    }
+
+    /// Sets whether focus traversal wraps from the last widget to the first and back, true by default.
+    /// Without wrapping, focus stays put at the ends.
+    pub fn set_focus_wrap(&mut self, wrap: bool) {
+        self.focus_wrap = wrap;
+    }
+
+    /// Enables directional focus navigation, off by default: move keys that no widget used this
+    /// frame jump focus to the nearest focusable widget in their direction, for gamepads.
+    /// With focus wrapping on, moving past the last widget in a row or column wraps to its other end.
+    pub fn set_directional_focus(&mut self, enabled: bool) {
+        self.directional_focus = enabled;
+    }
+
+    /// Marks the horizontal (left/right) and/or vertical (up/down) move keys as used this frame,
+    /// so directional focus navigation ignores them.
+    pub fn consume_move_keys(&mut self, horizontal: bool, vertical: bool) {
+        self.moves_consumed.0 |= horizontal;
+        self.moves_consumed.1 |= vertical;
+    }
+
+    /// Moves focus to `id` at the end of this frame, overriding keyboard traversal.
+    /// The widget is drawn focused from the next frame.
+    pub fn request_focus(&mut self, id: WidgetId) {
+        self.focus_request = Some(Some(id));
+    }
+
+    /// Clears focus at the end of this frame
+    pub fn blur(&mut self) {
+        self.focus_request = Some(None);
+    }
+
+    /// True if `id` is focused
+    pub fn is_focused(&self, id: WidgetId) -> bool {
+        self.state.focused == Some(id)
+    }
+
+    /// True if `id` became focused this frame, after not being focused last frame
+    pub fn focus_gained(&self, id: WidgetId) -> bool {
+        self.focus_at_start == Some(id) && self.state.last_focused != Some(id)
+    }
+
+    /// True if `id` stopped being focused this frame, after being focused last frame
+    pub fn focus_lost(&self, id: WidgetId) -> bool {
+        self.focus_at_start != Some(id) && self.state.last_focused == Some(id)
+    }
+
+    /// Registers `id` drawn at `rect` for focus traversal.
+    /// Returns true if the widget is currently focused.
+    /// Disabled widgets aren't registered and lose focus.
+    pub fn register_focusable(&mut self, id: WidgetId, rect: Rect) -> bool {
+        if self.disabled {
+            if self.state.focused == Some(id) {
+                self.state.focused = None;
+            }
+            return false;
+        }
+        let trap = self
+            .focus_scope_stack
+            .iter()
+            .rev()
+            .find(|scope| scope.trap)
+            .map(|scope| scope.id);
+        self.focusables.push((id, self.to_screen(rect), trap));
+        let focused = self.state.focused.is_some_and(|f| f == id);
+        if focused {
+            self.focused_seen = Some((self.clip_stack.len(), rect));
+            self.focused_scopes = self
+                .focus_scope_stack
+                .iter()
+                .map(|scope| scope.id)
+                .collect();
+        }
+        focused
+    }
+
+    /// Registers `shortcut` and returns true if it was pressed this frame, key repeats included.
+    /// When several places register the same shortcut, the one in the innermost focus scope around
+    /// the focused widget triggers, then ones outside any focus scope, then the first registered.
+    /// While a trapping focus scope is open, only shortcuts registered inside it trigger.
+    /// Conflicts are resolved from the previous frame's registrations, like hover. Until they are,
+    /// the first registration triggers; if the one that won isn't drawn, the next frame's does.
+    pub fn shortcut(&mut self, shortcut: Shortcut) -> bool {
+        let scope = self
+            .focus_scope_stack
+            .last()
+            .map_or(WidgetId::ROOT, |scope| scope.id);
+        // registering it again is a separate registration
+        let again = self
+            .shortcuts
+            .iter()
+            .filter(|(s, ..)| *s == shortcut)
+            .count();
+        let id = self.current_id().with(&(scope, shortcut, again));
+        self.register_shortcut(id, shortcut)
+    }
+
+    /// Gives the next button or menu item `shortcut`: it's drawn as accelerator text and
+    /// clicks the widget when pressed, see `shortcut`.
+    /// Menu items only respond while their menu is open, so register the same shortcut with
+    /// `shortcut` elsewhere to trigger it with the menu closed.
+    pub fn set_next_shortcut(&mut self, shortcut: Shortcut) {
+        self.next_shortcut = Some(shortcut);
+    }
+
+    /// Registers `shortcut` for `id`, returning true if it triggers it this frame
+    fn register_shortcut(&mut self, id: WidgetId, shortcut: Shortcut) -> bool {
+        let scopes: Vec<WidgetId> = self
+            .focus_scope_stack
+            .iter()
+            .map(|scope| scope.id)
+            .collect();
+        let trapped = self
+            .state
+            .focus_scopes
+            .iter()
+            .rev()
+            .find(|scope| scope.trap)
+            .is_some_and(|trap| !scopes.contains(&trap.id));
+        self.shortcuts.push((shortcut, id, scopes));
+        if self.disabled || trapped {
+            return false;
+        }
+
+        let pressed =
+            self.shortcut_pressed(shortcut) || self.state.shortcuts_pending.contains(&shortcut);
+        let winner = self
+            .state
+            .shortcut_winners
+            .iter()
+            .find(|(s, _)| *s == shortcut)
+            .map(|(_, winner)| *winner);
+        // without a winner, only the first registration this frame triggers
+        let triggered = pressed
+            && match winner {
+                Some(winner) => winner == id,
+                None => !self.shortcuts_triggered.contains(&shortcut),
+            };
+        if triggered {
+            self.shortcuts_triggered.push(shortcut);
+        }
+        triggered
+    }
+
+    /// A key event this frame matches `shortcut`
+    fn shortcut_pressed(&self, shortcut: Shortcut) -> bool {
+        self.input_state
+            .key_events
+            .iter()
+            .any(|event| shortcut.matches(event))
+    }
+
+    /// Picks which registration of each shortcut triggers next frame
+    fn resolve_shortcuts(&mut self, trap: Option<WidgetId>) {
+        let mut winners: Vec<(Shortcut, WidgetId, usize)> = vec![];
+        for (shortcut, id, scopes) in &self.shortcuts {
+            if trap.is_some_and(|trap| !scopes.contains(&trap)) {
+                continue;
+            }
+            // deeper scopes around focus score higher, scopes away from it lowest
+            let score = if self.focused_scopes.starts_with(scopes) {
+                scopes.len() + 1
+            } else {
+                0
+            };
+            match winners.iter_mut().find(|(s, ..)| s == shortcut) {
+                Some(winner) if score > winner.2 => *winner = (*shortcut, *id, score),
+                Some(_) => {}
+                None => winners.push((*shortcut, *id, score)),
+            }
+        }
+        // presses the winner missed go to the new one, once
+        self.state.shortcuts_pending = winners
+            .iter()
+            .map(|(shortcut, ..)| *shortcut)
+            .filter(|shortcut| {
+                self.shortcut_pressed(*shortcut) && !self.shortcuts_triggered.contains(shortcut)
+            })
+            .collect();
+        self.state.shortcut_winners = winners
+            .into_iter()
+            .map(|(shortcut, id, _)| (shortcut, id))
+            .collect();
+    }
+
+    /// The scroll offset of the current clip region
+    fn scroll_offset(&self) -> Vec2 {
+        self.clip_stack
+            .last()
+            .map_or(Vec2::zero(), |clip| clip.offset)
+    }
+
+    /// Translates `rect` from the current (possibly scrolled) space to the screen.
+    /// Parts scrolled above or left of the screen are cut off.
+    fn to_screen(&self, rect: Rect) -> Rect {
+        let offset = self.scroll_offset();
+        let top_left = Vec2::new(
+            rect.top_left.x.saturating_sub(offset.x),
+            rect.top_left.y.saturating_sub(offset.y),
+        );
+        let bottom_right = Vec2::new(
+            (rect.top_left.x + rect.size.x).saturating_sub(offset.x),
+            (rect.top_left.y + rect.size.y).saturating_sub(offset.y),
+        );
+        Rect {
+            top_left,
+            size: Vec2::sub(bottom_right, top_left),
+        }
+    }
+
+    /// The mouse position in the current (possibly scrolled) space
+    pub fn content_mouse_position(&self) -> Vec2 {
+        let offset = self.scroll_offset();
+        let mouse = self.input_state.mouse_position;
+        Vec2::new(
+            mouse.x.saturating_add(offset.x),
+            mouse.y.saturating_add(offset.y),
+        )
+    }
+
+    /// Wheel movement as `(x, y)` not yet used by a widget this frame.
+    /// See `UIInputState::wheel_y` for the direction.
+    pub fn wheel_delta(&self) -> (f32, f32) {
+        if self.wheel_consumed {
+            return (0.0, 0.0);
+        }
+        (self.input_state.wheel_x, self.input_state.wheel_y)
+    }
+
+    /// Marks the wheel as used this frame, so other widgets (such as an enclosing scroll area) ignore it.
+    pub fn consume_wheel(&mut self) {
+        self.wheel_consumed = true;
+    }
+
+    /// Consumes vertical wheel movement as whole notches for `id`.
+    /// Fractions of a notch carry over to later frames while `id` keeps receiving the wheel.
+    fn consume_wheel_notches(&mut self, id: WidgetId) -> i32 {
+        let (_, wheel_y) = self.wheel_delta();
+        if wheel_y == 0.0 {
+            return 0;
+        }
+        self.consume_wheel();
+        if self.state.wheel_target != Some(id) {
+            self.state.wheel_target = Some(id);
+            self.state.wheel_remainder = 0.0;
+        }
+        let total = self.state.wheel_remainder + wheel_y;
+        let notches = total.trunc();
+        self.state.wheel_remainder = total - notches;
+        notches as i32
+    }
+
+    /// False if the mouse is outside the current clip region
+    fn mouse_in_clip(&self) -> bool {
+        self.clip_stack
+            .last()
+            .is_none_or(|clip| clip.rect.contains(self.input_state.mouse_position))
+    }
+
+    /// The ID of the current scope on the ID stack
+    pub fn current_id(&self) -> WidgetId {
+        self.id_stack
+            .last()
+            .expect("get id: should always have a root id scope")
+            .id
+    }
+
+    /// Pushes a new scope onto the ID stack, derived from the current scope and `source`.
+    /// Widgets created until the matching `pop_id` are identified relative to it.
+    pub fn push_id<H: Hash + ?Sized>(&mut self, source: &H) {
+        let id = self.current_id().with(source);
+        self.id_stack.push(IdScope::new(id));
+    }
+
+    /// Pops the scope pushed by `push_id`.
+    pub fn pop_id(&mut self) {
+        assert!(
+            self.id_stack.len() > 1,
+            "pop id: cannot pop the root id scope"
+        );
+        self.id_stack.pop();
+    }
+
+    /// Executes `func` with `source` pushed onto the ID stack for the duration of the call.
+    pub fn with_id<H, F, T>(&mut self, source: &H, func: F) -> T
+    where
+        H: Hash + ?Sized,
+        F: FnOnce(&mut Self) -> T,
+    {
+        self.push_id(source);
+        let ret = func(self);
+        self.pop_id();
+        ret
+    }
+
+    /// Returns an ID derived from `label` relative to the current ID scope.
+    pub fn id_from_label(&self, label: &str) -> WidgetId {
+        self.current_id().with(label)
+    }
+
+    /// Uses `id` for the next widget or layout instead of an automatically numbered one.
+    pub fn set_next_id(&mut self, id: WidgetId) {
+        self.next_id = Some(id);
+    }
+
+    /// Takes the ID for the next widget, either set by `set_next_id` or numbered within the current scope.
+    fn make_id(&mut self) -> WidgetId {
+        let id = match self.next_id.take() {
+            Some(id) => id,
+            None => self
+                .id_stack
+                .last_mut()
+                .expect("make id: should always have a root id scope")
+                .next_auto_id(),
+        };
+        self.last_id = Some(id);
+        id
+    }
+
+    /// The ID of the widget or layout most recently started, for use with the focus functions
+    pub fn last_widget_id(&self) -> Option<WidgetId> {
+        self.last_id
+    }
+
+    /// Sets the classlist used for all draws to `class_list`.
+    /// Clear it using `clear_class_list` or see `with_class_list`.
+    pub fn set_class_list(&mut self, class_list: ClassList) {
+        self.next_class = Some(class_list);
+    }
+
+    /// Clears the currently used class list for drawing.
+    /// Set it using `set_class_list` or see `with_class_list`.
+    pub fn clear_class_list(&mut self) {
+        self.next_class = None;
+    }
+
+    /// Executes `func` providing this UI context and returning its result, with the `class_list` set for the duration of the call.
+    pub fn with_class_list<F, T>(&mut self, class_list: ClassList, func: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        self.set_class_list(class_list);
+        let ret = func(self);
+        self.clear_class_list();
+        ret
+    }
+
+    /// Sets whether the following widgets are disabled: drawn flagged `DISABLED`, but not
+    /// hovered, pressed or focused. See `with_disabled`.
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+    }
+
+    /// Executes `func` providing this UI context and returning its result, with widgets disabled for
+    /// the duration of the call if `disabled` is true. Widgets inside an already disabled scope stay disabled.
+    pub fn with_disabled<F, T>(&mut self, disabled: bool, func: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let prev = self.disabled;
+        self.disabled |= disabled;
+        let ret = func(self);
+        self.disabled = prev;
+        ret
+    }
+
+    /// Executes `func` providing this UI context and returning its result, with the widgets it draws
+    /// in a focus scope. When the scope stops being drawn, focus left inside it goes back to
+    /// where it was when the scope first appeared.
+    /// A trapping scope, such as a modal dialog, takes focus when it appears and confines
+    /// traversal to its widgets while open. The last trapping scope to open wins.
+    pub fn with_focus_scope<F, T>(&mut self, trap: bool, func: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let scope = FocusScope {
+            id: self.make_id(),
+            trap,
+            restore: None,
+        };
+        self.focus_scopes_drawn.push(scope);
+        self.focus_scope_stack.push(scope);
+        let ret = func(self);
+        self.focus_scope_stack.pop();
+        ret
+    }
+
+    /// The layer currently drawn to
+    pub fn current_layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// Executes `func` drawing to `layer` for the duration of the call.
+    /// The current clip and scroll don't apply inside: `func` draws in screen space, unclipped.
+    /// If `layer` is already the current layer, `func` simply runs as is.
+    pub fn with_layer<F, T>(&mut self, layer: Layer, func: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        if layer == self.layer {
+            return func(self);
+        }
+        let prev_layer = self.layer;
+        let prev_clips = std::mem::take(&mut self.clip_stack);
+        self.swap_layer(layer);
+        let ret = func(self);
+        debug_assert!(
+            self.clip_stack.is_empty(),
+            "with_layer: every push_clip should be popped"
+        );
+        self.swap_layer(prev_layer);
+        self.clip_stack = prev_clips;
+        ret
+    }
+
+    /// Parks the current layer's commands and takes out those of `layer`
+    fn swap_layer(&mut self, layer: Layer) {
+        let commands = std::mem::take(&mut self.layer_buffers[layer as usize]);
+        self.layer_buffers[self.layer as usize] =
+            std::mem::replace(&mut self.command_buffer, commands);
+        self.layer = layer;
+    }
+
+    /// Adds the flags every draw in the current scope carries
+    fn scope_flags(&self, mut flags: Flags) -> Flags {
+        if self.disabled {
+            flags |= flags::DISABLED;
+        }
+        flags
+    }
+
+    /// Returns the index into the command buffer of this draw
     pub fn rect_raw(&mut self, rect: Rect, flags: Flags, role: UIDrawRole) -> usize {
         let idx = self.command_buffer.len();
+        let flags = self.scope_flags(flags);
         self.command_buffer.push_back(DrawCommand::DrawRect {
             draw_data: DrawData {
                 rect,
@@ -469,6 +2448,7 @@ impl<'f> UIContext<'f> {
         role: UIDrawRole,
         scale: f32,
     ) {
+        let flags = self.scope_flags(flags);
         self.command_buffer.push_back(DrawCommand::DrawText {
             content: label,
             text_scale: scale,
@@ -488,29 +2468,23 @@ impl<'f> UIContext<'f> {
         padding: Vec2,
         label: String,
         text_scale: f32,
-    ) -> bool {
-        let button_size = Vec2::add(text_size, padding);
+    ) -> Response {
+        let content_size = self.button_content_size(text_size, text_scale);
+        let shortcut = self.next_shortcut.take();
+        let button_size = Vec2::add(content_size, padding);
         let rect = Rect {
             top_left,
             size: button_size,
         };
 
-        let hovered = self.check_set_hover(rect);
-        let active = self.is_active(rect);
-        let focused = self.register_focusable(rect);
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let focused = self.register_focusable(id, rect);
+        let triggered = shortcut.is_some_and(|shortcut| self.register_shortcut(id, shortcut));
 
-        let mut flags = flags::NONE;
-        if hovered {
-            flags |= flags::HOVER;
-        }
-        if active {
-            flags |= flags::ACTIVE;
-        }
-        if focused {
-            flags |= flags::FOCUSED;
-        }
+        let flags = self.interaction_flags(id, hovered, focused);
 
-        let half_padding = Vec2::div_cmp(Vec2::sub(rect.size, text_size), 2);
+        let half_padding = Vec2::div_cmp(Vec2::sub(rect.size, content_size), 2);
         let centered_text_pos = Vec2::add(rect.top_left, half_padding);
 
         self.rect_raw(rect, flags, UIDrawRole::ButtonBackground);
@@ -524,27 +2498,125 @@ impl<'f> UIContext<'f> {
             UIDrawRole::ButtonText,
             text_scale,
         );
+        // accelerator text follows the label
+        if let Some(shortcut) = shortcut {
+            let text = shortcut.to_string();
+            let size = self.font_info.compute_text_size(&text, text_scale);
+            let top_left = Vec2::new(
+                centered_text_pos.x + text_size.x + SHORTCUT_GAP,
+                rect.top_left.y + (rect.size.y - size.y) / 2,
+            );
+            self.text_raw(
+                text,
+                Rect { top_left, size },
+                flags,
+                UIDrawRole::ShortcutText,
+                text_scale,
+            );
+        }
+
+        let mut response = self.response(id, rect, hovered, focused);
+        response.clicked |= triggered;
+        response
+    }
+
+    /// Size of a button's label, and its accelerator text if the next widget has a shortcut
+    fn button_content_size(&self, text_size: Vec2, scale: f32) -> Vec2 {
+        match self.next_shortcut {
+            Some(shortcut) => {
+                let size = self
+                    .font_info
+                    .compute_text_size(&shortcut.to_string(), scale);
+                Vec2::new(text_size.x + SHORTCUT_GAP + size.x, text_size.y.max(size.y))
+            }
+            None => text_size,
+        }
+    }
+
+    fn is_active(&self, id: WidgetId) -> bool {
+        !self.disabled && self.state.active.is_some_and(|active| active == id)
+    }
+
+    /// The activate button is down from Enter or Space rather than the mouse,
+    /// see `UIInputState::apply_key_events`
+    fn activated_by_key(&self) -> bool {
+        self.state.activate_key_held || self.input_state.activation_key_pressed()
+    }
+
+    /// Pressed and still held, as opposed to the frame of the press
+    fn is_dragging(&self, id: WidgetId) -> bool {
+        self.input_state.activate_button == ButtonState::Held && self.is_active(id)
+    }
 
-        (hovered || focused) && self.clicked_rect(rect)
+    fn clicked(&self, id: WidgetId) -> bool {
+        self.input_state.activate_button == ButtonState::Released && self.is_active(id)
     }
 
-    fn is_active(&self, rect: Rect) -> bool {
-        self.state
-            .active_rect
-            .is_some_and(|active_rect| active_rect == rect)
+    /// The `HOVER`, `ACTIVE`, `FOCUSED` and `DRAGGING` flags of widget `id` this frame,
+    /// matching its `Response`
+    fn interaction_flags(&self, id: WidgetId, hovered: bool, focused: bool) -> Flags {
+        let mut flags = flags::NONE;
+        flags.set(flags::HOVER, hovered);
+        flags.set(flags::ACTIVE, self.is_active(id));
+        flags.set(flags::FOCUSED, focused);
+        flags.set(flags::DRAGGING, self.is_dragging(id));
+        flags
     }
 
-    fn clicked_rect(&self, rect: Rect) -> bool {
-        self.input_state.activate_button == ButtonState::Released && self.is_active(rect)
+    /// The interaction of widget `id` drawn at `rect` this frame, the same for every widget.
+    /// Widgets set `changed` themselves.
+    fn response(&mut self, id: WidgetId, rect: Rect, hovered: bool, focused: bool) -> Response {
+        self.measures.insert(
+            id,
+            Measure {
+                size: rect.size,
+                ..Default::default()
+            },
+        );
+        let clicked = (hovered || focused) && self.clicked(id);
+        let mut double_clicked = false;
+        if clicked {
+            let time = self.input_state.time;
+            double_clicked = self
+                .state
+                .last_click
+                .is_some_and(|(last, at)| last == id && time - at <= DOUBLE_CLICK_TIME);
+            // a third click starts over
+            self.state.last_click = (!double_clicked).then_some((id, time));
+        }
+        let dragging = self.is_dragging(id);
+        let was_dragging = self.state.dragged == Some(id);
+
+        Response {
+            id,
+            rect,
+            hovered,
+            pressed: hovered && self.input_state.activate_button == ButtonState::Down,
+            clicked,
+            double_clicked,
+            secondary_clicked: hovered
+                && self.input_state.secondary_button == ButtonState::Released,
+            middle_clicked: hovered && self.input_state.middle_button == ButtonState::Released,
+            drag_started: dragging && !was_dragging,
+            dragging,
+            drag_stopped: was_dragging && !dragging,
+            focus_gained: self.focus_gained(id),
+            focus_lost: self.focus_lost(id),
+            changed: false,
+        }
     }
 
-    fn check_set_hover(&mut self, rect: Rect) -> bool {
-        let is_hover = rect.contains(self.input_state.mouse_position);
-        if is_hover {
-            self.hover_rect = Some(rect);
+    /// Hit tests `id` drawn at `rect`, entering it for this frame's hover.
+    /// Returns true if the mouse is over it and it was hovered last frame, so widgets
+    /// covered by ones drawn later or on higher layers never report hover.
+    /// Disabled widgets still cover what's under them, but never report hover.
+    fn check_set_hover(&mut self, id: WidgetId, rect: Rect) -> bool {
+        let hit = self.mouse_in_clip() && rect.contains(self.content_mouse_position());
+        if hit {
+            self.hits.push((id, self.layer));
         }
 
-        is_hover
+        hit && !self.disabled && self.state.hovered == Some(id)
     }
 
     pub fn text(&mut self, label: String, rect: Rect) {
@@ -579,13 +2651,12 @@ impl<'f> UIContext<'f> {
     }
 
     pub fn text_layout_scaled(&mut self, label: String, scale: f32) -> Vec2 {
-        let layout = self.get_current_layout();
         let text_size = self.font_info.compute_text_size(&label, scale);
         self.text_scaled(
             label,
             Rect {
                 size: text_size,
-                top_left: layout.top_left,
+                top_left: self.layout_child_rect(text_size).top_left,
             },
             scale,
         );
@@ -593,7 +2664,7 @@ impl<'f> UIContext<'f> {
         text_size
     }
 
-    pub fn button(&mut self, top_left: Vec2, padding: Vec2, label: String) -> bool {
+    pub fn button(&mut self, top_left: Vec2, padding: Vec2, label: String) -> Response {
         self.button_scaled(top_left, padding, label, 1.0)
     }
 
@@ -603,47 +2674,47 @@ impl<'f> UIContext<'f> {
         padding: Vec2,
         label: String,
         scale: f32,
-    ) -> bool {
+    ) -> Response {
         let text_size = self.font_info.compute_text_size(&label, scale);
         self.button_raw(top_left, text_size, padding, label, scale)
     }
 
-    pub fn button_layout(&mut self, padding: Vec2, label: String) -> bool {
+    pub fn button_layout(&mut self, padding: Vec2, label: String) -> Response {
         self.button_layout_scaled(padding, label, 1.0)
     }
 
-    pub fn button_layout_scaled(&mut self, padding: Vec2, label: String, scale: f32) -> bool {
-        let layout = self.get_current_layout();
+    pub fn button_layout_scaled(&mut self, padding: Vec2, label: String, scale: f32) -> Response {
         let text_size = self.font_info.compute_text_size(&label, scale);
-        let clicked = self.button_raw(layout.top_left, text_size, padding, label, scale);
-        self.recompute_current_layout(Vec2::add(text_size, padding));
-        clicked
+        let size = Vec2::add(self.button_content_size(text_size, scale), padding);
+        let rect = self.layout_child_rect(size);
+        // stretched and filling buttons center their label in the extra space
+        let extra = Vec2::new(
+            rect.size.x.saturating_sub(size.x),
+            rect.size.y.saturating_sub(size.y),
+        );
+        let padding = Vec2::add(padding, extra);
+        let response = self.button_raw(rect.top_left, text_size, padding, label, scale);
+        self.recompute_current_layout(size);
+        response
     }
 
     /// Draws a checkbox at `top_left` with a given box `size`.
-    /// Returns true when the checkbox toggles, and mutates the caller-held `checked` value.
-    pub fn checkbox(&mut self, top_left: Vec2, size: Vec2, checked: &mut bool) -> bool {
+    /// Mutates the caller-held `checked` value, `changed` when it toggles.
+    pub fn checkbox(&mut self, top_left: Vec2, size: Vec2, checked: &mut bool) -> Response {
         let rect = Rect { top_left, size };
 
-        let hovered = self.check_set_hover(rect);
-        let active = self.is_active(rect);
-        let focused = self.register_focusable(rect);
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let focused = self.register_focusable(id, rect);
 
-        let mut flags = flags::NONE;
-        if hovered {
-            flags |= flags::HOVER;
-        }
-        if active {
-            flags |= flags::ACTIVE;
-        }
-        if focused {
-            flags |= flags::FOCUSED;
-        }
+        let mut flags = self.interaction_flags(id, hovered, focused);
 
-        let toggled = (hovered || focused) && self.clicked_rect(rect);
-        if toggled {
+        let mut response = self.response(id, rect, hovered, focused);
+        if response.clicked {
             *checked = !*checked;
+            response.changed = true;
         }
+        flags.set(flags::CHECKED, *checked);
 
         self.rect_raw(rect, flags, UIDrawRole::CheckboxBox);
 
@@ -664,123 +2735,547 @@ impl<'f> UIContext<'f> {
             );
         }
 
-        toggled
+        response
     }
 
     /// Draws a checkbox using the current layout position.
-    pub fn checkbox_layout(&mut self, size: Vec2, checked: &mut bool) -> CheckboxResult {
-        let top_left = self.get_current_layout().top_left;
-        let toggled = self.checkbox(top_left, size, checked);
+    pub fn checkbox_layout(&mut self, size: Vec2, checked: &mut bool) -> Response {
+        let top_left = self.layout_child_rect(size).top_left;
+        let response = self.checkbox(top_left, size, checked);
         self.recompute_current_layout(size);
-        CheckboxResult {
-            interacted: toggled,
-            rect: Rect { top_left, size }
-        }
+        response
     }
 
     /// Draws a checkbox using the current layout, and `label` centered on the left.
-    /// `label_width` is required to ensure the layout remains stable even if the text width changes.
-    pub fn checkbox_layout_label_left(&mut self, size: Vec2, checked: &mut bool, label: String, label_scale: f32, label_width: u32) -> bool {
+    /// Left labels in the same layout are as wide as the widest, so the checkboxes line up.
+    pub fn checkbox_layout_label_left(
+        &mut self,
+        size: Vec2,
+        checked: &mut bool,
+        label: String,
+        label_scale: f32,
+    ) -> Response {
+        let label_width = self.label_column(&label, label_scale);
         self.layout(LayoutDirection::Horizontal, None, false, |ui| {
-            let layout = *ui.get_current_layout();
-            let text_size = ui.font_info.compute_text_size(&label, label_scale);
-            // add half the size y to center the text
-            let label_top_left = Vec2::add(layout.top_left, Vec2::new(0, (size.y.saturating_sub(text_size.y)) / 2));
-            ui.text_at_scaled(label, label_top_left, label_scale);
-            
-            // Use fixed width for layout advancement
-            ui.recompute_current_layout(Vec2::new(label_width, text_size.y));
-
-            // now draw checkbox next to it
-
-            let interacted = ui.checkbox_layout(size, checked);
-            interacted.interacted
+            ui.layout_label(label, label_width, size.y, label_scale);
+            ui.checkbox_layout(size, checked)
         })
     }
 
     /// Draws a checkbox using the current layout, and `label` centered on the right.
-    /// `label_width` is required to ensure the layout remains stable even if the text width changes.
-    pub fn checkbox_layout_label_right(&mut self, size: Vec2, checked: &mut bool, label: String, label_scale: f32, label_width: u32) -> bool {
+    pub fn checkbox_layout_label_right(
+        &mut self,
+        size: Vec2,
+        checked: &mut bool,
+        label: String,
+        label_scale: f32,
+    ) -> Response {
         self.layout(LayoutDirection::Horizontal, None, false, |ui| {
-            let interacted = ui.checkbox_layout(size, checked);
-
-            let layout = *ui.get_current_layout();
-            let text_size = ui.font_info.compute_text_size(&label, label_scale);
-            // add half the size y to center the text
-            let label_top_left = Vec2::add(layout.top_left, Vec2::new(0, (size.y.saturating_sub(text_size.y)) / 2));
-            ui.text_at_scaled(label, label_top_left, label_scale);
-            
-            // Use fixed width
-            ui.recompute_current_layout(Vec2::new(label_width, text_size.y));
-            
-            interacted.interacted
+            let response = ui.checkbox_layout(size, checked);
+            let label_width = ui.font_info.compute_text_size(&label, label_scale).x;
+            ui.layout_label(label, label_width, size.y, label_scale);
+            response
         })
     }
-    /// Returns true if the slider value changed
-    pub fn slider<T: SliderValue>(&mut self, rect: Rect, state: &mut SliderState<T>) -> bool {
-        let hovered = self.check_set_hover(rect);
-        let is_active = self.is_active(rect);
-        let focused = self.register_focusable(rect);
-        let knob_size = Vec2::new(10, rect.size.y);
 
-        let val = state.value;
+    /// Width of a column holding `label` and the other left labels of the current layout: the
+    /// widest drawn so far this frame or measured last frame.
+    fn label_column(&mut self, label: &str, scale: f32) -> u32 {
+        let width = self.font_info.compute_text_size(label, scale).x;
+        let layout = self
+            .layout_stack
+            .last_mut()
+            .expect("label column: should always have a root layout");
+        layout.measure.label_width = layout.measure.label_width.max(width);
+        let last = self
+            .state
+            .measures
+            .get(&layout.id)
+            .map_or(0, |measure| measure.label_width);
+        layout.measure.label_width.max(last)
+    }
 
-        // by how many pixels does each step of the slider correspond to
-        let slider_span = rect.size.x.saturating_sub(knob_size.x);
-        let pixels_per_step = if slider_span == 0 {
-            0.0
-        } else {
-            slider_span as f32 * T::step_percentage(state.step, state.min, state.max)
-        };
+    /// Draws `label` in the current layout, taking `width` and centered on a widget `height` tall
+    fn layout_label(&mut self, label: String, width: u32, height: u32, scale: f32) {
+        let text_size = self.font_info.compute_text_size(&label, scale);
+        let top_left = Vec2::add(
+            self.layout_child_rect(text_size).top_left,
+            Vec2::new(0, height.saturating_sub(text_size.y) / 2),
+        );
+        self.text_at_scaled(label, top_left, scale);
+        self.recompute_current_layout(Vec2::new(width, text_size.y));
+    }
 
-        if is_active {
-            // build drag value over this draw
-            let delta_x =
-                self.input_state.mouse_position.x as f32 - self.state.last_mouse_position.x as f32;
-            self.state.active_drag_amt += delta_x;
+    /// Draws a radio button at `top_left` with a given `size`, selecting `option` when clicked.
+    /// Radios editing the same `value` form a group, which arrow keys move through when one is focused.
+    /// `changed` when this radio becomes selected.
+    pub fn radio<T: PartialEq + Copy + 'static>(
+        &mut self,
+        top_left: Vec2,
+        size: Vec2,
+        value: &mut T,
+        option: T,
+    ) -> Response {
+        let rect = Rect { top_left, size };
 
-            if pixels_per_step > 0.0 {
-                // increment n steps based on the amount dragged
-                let steps = (self.state.active_drag_amt / pixels_per_step).trunc();
-                let steps_i = steps as i32;
-                if steps_i != 0 {
-                    if steps_i > 0 {
-                        for _ in 0..steps_i {
-                            state.value =
-                                T::increment(state.value, state.step, state.min, state.max);
-                        }
-                    } else {
-                        for _ in 0..(-steps_i) {
-                            state.value =
-                                T::decrement(state.value, state.step, state.min, state.max);
-                        }
-                    }
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let focused = self.register_focusable(id, rect);
+        // a value and its first field share an address, so the type tells their groups apart
+        let group = (value as *const T as usize, TypeId::of::<T>());
+        if !self.disabled {
+            self.radios.push((group, id));
+        }
 
-                    // keep remainder of drag
-                    self.state.active_drag_amt -= steps * pixels_per_step;
-                }
-            } else {
-                // increment once on any drag
-                if self.state.active_drag_amt > 0.0 {
-                    state.value = T::increment(state.value, state.step, state.min, state.max);
-                } else if self.state.active_drag_amt < 0.0 {
-                    state.value = T::decrement(state.value, state.step, state.min, state.max);
-                }
-                self.state.active_drag_amt = 0.0;
-            }
+        let mut flags = self.interaction_flags(id, hovered, focused);
+
+        // arrow keys select the radio they move focus to
+        let arrowed_to = self
+            .state
+            .radio_selected
+            .take_if(|selected| *selected == id);
+        let mut response = self.response(id, rect, hovered, focused);
+        if response.clicked || arrowed_to.is_some() {
+            response.changed = *value != option;
+            *value = option;
         }
-        state.value = T::clamp_value(state.value, state.min, state.max);
-        let interacted = !SliderValue::equals(val, state.value);
-        let value_percentage =
-            T::percentage(state.value, state.min, state.max).clamp(0.0_f32, 1.0_f32);
-        let mut flags = flags::NONE;
-        if hovered {
-            flags |= flags::HOVER;
+        flags.set(flags::CHECKED, *value == option);
+
+        self.rect_raw(rect, flags, UIDrawRole::RadioOuter);
+
+        if *value == option {
+            let inset = Vec2::new(size.x / 4, size.y / 4);
+            let dot_size = Vec2 {
+                x: size.x.saturating_sub(inset.x.saturating_mul(2)),
+                y: size.y.saturating_sub(inset.y.saturating_mul(2)),
+            };
+            self.rect_raw(
+                Rect {
+                    top_left: Vec2::add(rect.top_left, inset),
+                    size: dot_size,
+                },
+                flags,
+                UIDrawRole::RadioDot,
+            );
         }
-        if focused {
-            flags |= flags::FOCUSED;
+
+        response
+    }
+
+    /// Draws a radio button using the current layout position.
+    pub fn radio_layout<T: PartialEq + Copy + 'static>(
+        &mut self,
+        size: Vec2,
+        value: &mut T,
+        option: T,
+    ) -> Response {
+        let top_left = self.layout_child_rect(size).top_left;
+        let response = self.radio(top_left, size, value, option);
+        self.recompute_current_layout(size);
+        response
+    }
+
+    /// Draws a radio button using the current layout, and `label` centered on the left.
+    /// Left labels in the same layout are as wide as the widest, so the radios line up.
+    pub fn radio_layout_label_left<T: PartialEq + Copy + 'static>(
+        &mut self,
+        size: Vec2,
+        value: &mut T,
+        option: T,
+        label: String,
+        label_scale: f32,
+    ) -> Response {
+        let label_width = self.label_column(&label, label_scale);
+        self.layout(LayoutDirection::Horizontal, None, false, |ui| {
+            ui.layout_label(label, label_width, size.y, label_scale);
+            ui.radio_layout(size, value, option)
+        })
+    }
+
+    /// Draws a radio button using the current layout, and `label` centered on the right.
+    pub fn radio_layout_label_right<T: PartialEq + Copy + 'static>(
+        &mut self,
+        size: Vec2,
+        value: &mut T,
+        option: T,
+        label: String,
+        label_scale: f32,
+    ) -> Response {
+        self.layout(LayoutDirection::Horizontal, None, false, |ui| {
+            let response = ui.radio_layout(size, value, option);
+            let label_width = ui.font_info.compute_text_size(&label, label_scale).x;
+            ui.layout_label(label, label_width, size.y, label_scale);
+            response
+        })
+    }
+
+    /// Draws a combo box in `rect` showing the label of the selected `value`.
+    /// Activating it opens a popup list of `options` on the popup layer, which
+    /// `move_up`/`move_down` and the mouse highlight. Activating the popup or the combo box again picks
+    /// the highlighted option, while clicking outside or `cancel_button` closes it unchanged.
+    /// `changed` when `value` changes.
+    pub fn combo_box<T: PartialEq + Copy>(
+        &mut self,
+        rect: Rect,
+        value: &mut T,
+        options: &[(T, &str)],
+    ) -> Response {
+        self.combo_box_scaled(rect, value, options, 1.0)
+    }
+
+    pub fn combo_box_scaled<T: PartialEq + Copy>(
+        &mut self,
+        rect: Rect,
+        value: &mut T,
+        options: &[(T, &str)],
+        scale: f32,
+    ) -> Response {
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let focused = self.register_focusable(id, rect);
+        let current = options.iter().position(|(option, _)| option == value);
+
+        // the popup hangs below the combo box, in screen space
+        let screen_rect = self.to_screen(rect);
+        let popup = Rect {
+            top_left: Vec2::new(
+                screen_rect.top_left.x,
+                screen_rect.top_left.y + screen_rect.size.y,
+            ),
+            size: Vec2::new(rect.size.x, rect.size.y * options.len() as u32),
+        };
+        let item_rect = |idx: usize| Rect {
+            top_left: Vec2::new(
+                popup.top_left.x,
+                popup.top_left.y + idx as u32 * rect.size.y,
+            ),
+            size: rect.size,
+        };
+
+        let mut open = !self.disabled && self.state.open_popup == Some(id);
+        let hovered_item = if open {
+            self.with_layer(Layer::Popup, |ui| {
+                ui.check_set_hover(id.with("popup"), popup);
+                (0..options.len())
+                    .filter(|idx| ui.check_set_hover(id.with(idx), item_rect(*idx)))
+                    .last()
+            })
+        } else {
+            None
+        };
+        // presses outside close the popup, whatever was hovered last frame
+        let over_combo = self.mouse_in_clip() && rect.contains(self.content_mouse_position());
+        let over_popup = popup.contains(self.input_state.mouse_position);
+
+        let mut response = self.response(id, rect, hovered, focused);
+        let mut picked = None;
+        if response.clicked {
+            if open {
+                picked = Some(self.state.popup_highlight);
+                open = false;
+            } else {
+                open = true;
+                self.state.popup_highlight = current.unwrap_or_default();
+                self.state.focused = Some(id);
+            }
+        } else if open {
+            let input = &self.input_state;
+            if let Some(idx) = hovered_item {
+                if input.mouse_position != self.state.last_mouse_position {
+                    self.state.popup_highlight = idx;
+                }
+                if self.clicked(id.with(&idx)) {
+                    picked = Some(idx);
+                    open = false;
+                }
+            } else if input.activate_button == ButtonState::Down && !over_combo && !over_popup {
+                open = false;
+            }
+            if input.cancel_button == ButtonState::Down {
+                open = false;
+                self.cancel_consumed = true;
+            }
+            if input.move_down == ButtonState::Down {
+                self.state.popup_highlight =
+                    (self.state.popup_highlight + 1).min(options.len().saturating_sub(1));
+            }
+            if input.move_up == ButtonState::Down {
+                self.state.popup_highlight = self.state.popup_highlight.saturating_sub(1);
+            }
+            self.consume_move_keys(false, true);
+        }
+
+        if let Some((option, _)) = picked.and_then(|idx| options.get(idx)) {
+            response.changed = *value != *option;
+            *value = *option;
+        }
+
+        let mut flags = self.interaction_flags(id, hovered, focused);
+        if open {
+            flags |= flags::OPEN;
+        }
+
+        self.rect_raw(rect, flags, UIDrawRole::ComboBoxBackground);
+        let label = options
+            .iter()
+            .find(|(option, _)| option == value)
+            .map_or("", |(_, label)| *label);
+        self.combo_label(label, rect, flags, UIDrawRole::ComboBoxText, scale);
+        let arrow_size = rect.size.y / 2;
+        self.rect_raw(
+            Rect {
+                top_left: Vec2::new(
+                    (rect.top_left.x + rect.size.x).saturating_sub(arrow_size + arrow_size / 2),
+                    rect.top_left.y + (rect.size.y - arrow_size) / 2,
+                ),
+                size: Vec2::new(arrow_size, arrow_size),
+            },
+            flags,
+            UIDrawRole::ComboBoxArrow,
+        );
+
+        if open {
+            self.state.open_popup = Some(id);
+            self.popup_drawn = true;
+
+            let highlight = self.state.popup_highlight;
+            self.with_layer(Layer::Popup, |ui| {
+                ui.rect_raw(popup, flags::OPEN, UIDrawRole::PopupBackground);
+                for (idx, (option, label)) in options.iter().enumerate() {
+                    let mut item_flags = flags::NONE;
+                    if highlight == idx {
+                        item_flags |= flags::HOVER;
+                    }
+                    if option == value {
+                        item_flags |= flags::SELECTED;
+                    }
+                    if ui.is_active(id.with(&idx)) {
+                        item_flags |= flags::ACTIVE;
+                    }
+                    let item = item_rect(idx);
+                    ui.rect_raw(item, item_flags, UIDrawRole::PopupItem);
+                    ui.combo_label(label, item, item_flags, UIDrawRole::PopupItemText, scale);
+                }
+            });
+        } else if self.state.open_popup == Some(id) {
+            self.state.open_popup = None;
+        }
+
+        response
+    }
+
+    /// Draws `label` inset from the left of `rect` and centered vertically
+    fn combo_label(&mut self, label: &str, rect: Rect, flags: Flags, role: UIDrawRole, scale: f32) {
+        let text_size = self.font_info.compute_text_size(label, scale);
+        let top_left = Vec2::new(
+            rect.top_left.x + TEXT_INPUT_INSET,
+            rect.top_left.y + rect.size.y.saturating_sub(text_size.y) / 2,
+        );
+        self.text_raw(
+            label.to_string(),
+            Rect {
+                top_left,
+                size: text_size,
+            },
+            flags,
+            role,
+            scale,
+        );
+    }
+
+    /// Opens a context menu at the mouse when the widget of `response` is secondary clicked.
+    /// The menu is drawn `width` wide on the popup layer while open, with `func` drawing its
+    /// contents, usually `menu_item`s. Choosing an item, pressing outside or `cancel_button` closes it.
+    /// Returns the result of `func` if the menu was drawn.
+    pub fn context_menu<F, T>(&mut self, response: &Response, width: u32, func: F) -> Option<T>
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        if response.secondary_clicked && !self.disabled {
+            self.state.context_menu = Some((response.id, self.input_state.mouse_position));
+        }
+        let (owner, top_left) = self.state.context_menu?;
+        if owner != response.id {
+            return None;
+        }
+        self.context_menu_drawn = true;
+
+        let prev_width = std::mem::replace(&mut self.menu_width, width);
+        // the menu floats in a layout of its own, leaving the layout it's drawn from alone
+        self.layout_stack.push(Layout::new(
+            LayoutDirection::Vertical,
+            0,
+            top_left,
+            Vec2::zero(),
+        ));
+        let ret = self.with_layer(Layer::Popup, |ui| {
+            // the menu's ID mustn't shift widgets drawn after it
+            ui.set_next_id(owner.with("context menu"));
+            ui.layout_with_background(
+                Some(top_left),
+                LayoutDirection::Vertical,
+                0,
+                Some((UIDrawRole::PopupBackground, flags::OPEN)),
+                func,
+            )
+        });
+        let menu = self
+            .layout_stack
+            .pop()
+            .expect("context menu: should have popped the menu's layout");
+        self.menu_width = prev_width;
+
+        let input = &self.input_state;
+        let over_menu = Rect {
+            top_left,
+            size: menu.size,
+        }
+        .contains(input.mouse_position);
+        let pressed_outside = [input.activate_button, input.secondary_button]
+            .contains(&ButtonState::Down)
+            && !over_menu;
+        let cancelled = input.cancel_button == ButtonState::Down;
+        if cancelled {
+            self.cancel_consumed = true;
+        }
+        if std::mem::take(&mut self.menu_item_chosen) || pressed_outside || cancelled {
+            self.state.context_menu = None;
+        }
+        Some(ret)
+    }
+
+    /// Draws an item of the context menu being drawn, which closes the menu when clicked.
+    pub fn menu_item(&mut self, label: String) -> Response {
+        self.menu_item_scaled(label, 1.0)
+    }
+
+    pub fn menu_item_scaled(&mut self, label: String, scale: f32) -> Response {
+        let text_size = self.font_info.compute_text_size(&label, scale);
+        let size = Vec2::new(self.menu_width, text_size.y + MENU_ITEM_PADDING);
+        let rect = self.layout_child_rect(size);
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let shortcut = self.next_shortcut.take();
+        let triggered = shortcut.is_some_and(|shortcut| self.register_shortcut(id, shortcut));
+        let mut response = self.response(id, rect, hovered, false);
+        response.clicked |= triggered;
+        if response.clicked {
+            self.menu_item_chosen = true;
+        }
+
+        let flags = self.interaction_flags(id, hovered, false);
+        self.rect_raw(rect, flags, UIDrawRole::PopupItem);
+        self.combo_label(&label, rect, flags, UIDrawRole::PopupItemText, scale);
+        // accelerator text sits at the right edge
+        if let Some(shortcut) = shortcut {
+            let text = shortcut.to_string();
+            let size = self.font_info.compute_text_size(&text, scale);
+            let top_left = Vec2::new(
+                (rect.top_left.x + rect.size.x).saturating_sub(size.x + TEXT_INPUT_INSET),
+                rect.top_left.y + rect.size.y.saturating_sub(size.y) / 2,
+            );
+            self.text_raw(
+                text,
+                Rect { top_left, size },
+                flags,
+                UIDrawRole::ShortcutText,
+                scale,
+            );
+        }
+        self.recompute_current_layout(size);
+        response
+    }
+
+    /// Draws a combo box using the current layout position.
+    pub fn combo_box_layout<T: PartialEq + Copy>(
+        &mut self,
+        size: Vec2,
+        value: &mut T,
+        options: &[(T, &str)],
+    ) -> Response {
+        self.combo_box_layout_scaled(size, value, options, 1.0)
+    }
+
+    pub fn combo_box_layout_scaled<T: PartialEq + Copy>(
+        &mut self,
+        size: Vec2,
+        value: &mut T,
+        options: &[(T, &str)],
+        scale: f32,
+    ) -> Response {
+        let rect = self.layout_child_rect(size);
+        let response = self.combo_box_scaled(rect, value, options, scale);
+        self.recompute_current_layout(size);
+        response
+    }
+
+    /// `changed` if the slider value changed
+    pub fn slider<T: SliderValue>(&mut self, rect: Rect, state: &mut SliderState<T>) -> Response {
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let is_active = self.is_active(id);
+        let focused = self.register_focusable(id, rect);
+        let knob_size = Vec2::new(10, rect.size.y);
+
+        let val = state.value;
+
+        // by how many pixels does each step of the slider correspond to
+        let slider_span = rect.size.x.saturating_sub(knob_size.x);
+        let pixels_per_step = if slider_span == 0 {
+            0.0
+        } else {
+            slider_span as f32 * T::step_percentage(state.step, state.min, state.max)
+        };
+
+        if is_active {
+            // build drag value over this draw
+            let delta_x =
+                self.input_state.mouse_position.x as f32 - self.state.last_mouse_position.x as f32;
+            self.state.active_drag_amt += delta_x;
+
+            if pixels_per_step > 0.0 {
+                // increment n steps based on the amount dragged
+                let steps = (self.state.active_drag_amt / pixels_per_step).trunc();
+                let steps_i = steps as i32;
+                if steps_i != 0 {
+                    if steps_i > 0 {
+                        for _ in 0..steps_i {
+                            state.value =
+                                T::increment(state.value, state.step, state.min, state.max);
+                        }
+                    } else {
+                        for _ in 0..(-steps_i) {
+                            state.value =
+                                T::decrement(state.value, state.step, state.min, state.max);
+                        }
+                    }
+
+                    // keep remainder of drag
+                    self.state.active_drag_amt -= steps * pixels_per_step;
+                }
+            } else {
+                // increment once on any drag
+                if self.state.active_drag_amt > 0.0 {
+                    state.value = T::increment(state.value, state.step, state.min, state.max);
+                } else if self.state.active_drag_amt < 0.0 {
+                    state.value = T::decrement(state.value, state.step, state.min, state.max);
+                }
+                self.state.active_drag_amt = 0.0;
+            }
+        }
+        // each wheel notch over the slider is a step
+        if hovered {
+            let notches = self.consume_wheel_notches(id);
+            for _ in 0..notches.unsigned_abs() {
+                state.value = if notches > 0 {
+                    T::increment(state.value, state.step, state.min, state.max)
+                } else {
+                    T::decrement(state.value, state.step, state.min, state.max)
+                };
+            }
         }
 
+        // left and right step the focused slider
         if focused {
             if self.input_state.move_left == ButtonState::Down {
                 state.value = T::decrement(state.value, state.step, state.min, state.max);
@@ -788,8 +3283,15 @@ impl<'f> UIContext<'f> {
             if self.input_state.move_right == ButtonState::Down {
                 state.value = T::increment(state.value, state.step, state.min, state.max);
             }
+            self.consume_move_keys(true, false);
         }
 
+        state.value = T::clamp_value(state.value, state.min, state.max);
+        let interacted = !SliderValue::equals(val, state.value);
+        let value_percentage =
+            T::percentage(state.value, state.min, state.max).clamp(0.0_f32, 1.0_f32);
+        let flags = self.interaction_flags(id, hovered, focused);
+
         // move the knob by the percentage it is into the slider rect
         let knob_top_left = Vec2::add(
             rect.top_left,
@@ -808,503 +3310,3312 @@ impl<'f> UIContext<'f> {
             flags,
             UIDrawRole::SliderKnob,
         );
-        interacted
+        Response {
+            changed: interacted,
+            ..self.response(id, rect, hovered, focused)
+        }
     }
 
-    pub fn slider_layout<T: SliderValue>(&mut self, size: Vec2, state: &mut SliderState<T>) -> bool {
-        let layout = self.get_current_layout();
-        let interacted = self.slider(
-            Rect {
-                top_left: layout.top_left,
-                size,
-            },
-            state,
-        );
+    pub fn slider_layout<T: SliderValue>(
+        &mut self,
+        size: Vec2,
+        state: &mut SliderState<T>,
+    ) -> Response {
+        let rect = self.layout_child_rect(size);
+        let response = self.slider(rect, state);
         self.recompute_current_layout(size);
-        interacted
+        response
     }
 
     /// Draws a slider using the current layout, and `label` centered on the left.
-    /// `label_width` is required to ensure the layout remains stable even if the text width changes.
+    /// Left labels in the same layout are as wide as the widest, so the sliders line up.
     pub fn slider_layout_label_left<T: SliderValue>(
         &mut self,
         size: Vec2,
         state: &mut SliderState<T>,
         label: String,
         label_scale: f32,
-        label_width: u32,
-    ) -> bool {
+    ) -> Response {
+        let label_width = self.label_column(&label, label_scale);
         self.layout(LayoutDirection::Horizontal, None, false, |ui| {
-            let layout = *ui.get_current_layout();
-            let text_size = ui.font_info.compute_text_size(&label, label_scale);
-            // add half the size y to center the text
-            let label_top_left = Vec2::add(
-                layout.top_left,
-                Vec2::new(0, (size.y.saturating_sub(text_size.y)) / 2),
-            );
-            ui.text_at_scaled(label, label_top_left, label_scale);
-            
-            // Use fixed width
-            ui.recompute_current_layout(Vec2::new(label_width, text_size.y));
-
-            // now draw slider next to it
+            ui.layout_label(label, label_width, size.y, label_scale);
             ui.slider_layout(size, state)
         })
     }
 
     /// Draws a slider using the current layout, and `label` centered on the right.
-    /// `label_width` is required to ensure the layout remains stable even if the text width changes.
     pub fn slider_layout_label_right<T: SliderValue>(
         &mut self,
         size: Vec2,
         state: &mut SliderState<T>,
         label: String,
         label_scale: f32,
-        label_width: u32,
-    ) -> bool {
+    ) -> Response {
         self.layout(LayoutDirection::Horizontal, None, false, |ui| {
-            let interacted = ui.slider_layout(size, state);
-
-            let layout = *ui.get_current_layout();
-            let text_size = ui.font_info.compute_text_size(&label, label_scale);
-            // add half the size y to center the text
-            let label_top_left = Vec2::add(
-                layout.top_left,
-                Vec2::new(0, (size.y.saturating_sub(text_size.y)) / 2),
-            );
-            ui.text_at_scaled(label, label_top_left, label_scale);
-            
-            // Use fixed width
-            ui.recompute_current_layout(Vec2::new(label_width, text_size.y));
-            interacted
+            let response = ui.slider_layout(size, state);
+            let label_width = ui.font_info.compute_text_size(&label, label_scale).x;
+            ui.layout_label(label, label_width, size.y, label_scale);
+            response
         })
     }
 
-    /// Runs `F` inside a layout, using the current layout.
-    /// If `spacing` is `None` it will use the current layout spacing.
-    pub fn layout<F, T>(
-        &mut self,
-        direction: LayoutDirection,
-        spacing: Option<u32>,
-        with_bg: bool,
-        draw: F,
-    ) -> T
-    where
-        F: FnOnce(&mut Self) -> T,
-    {
-        let current_layout = self.get_current_layout();
-        self.layout_at(
-            current_layout.top_left,
-            direction,
-            spacing.unwrap_or(current_layout.spacing),
-            with_bg,
-            draw,
-        )
+    /// Draws a single line text field editing the caller-held `state` inside `rect`.
+    /// `changed` if the text changed.
+    pub fn text_input(&mut self, rect: Rect, state: &mut TextInputState) -> Response {
+        self.text_input_scaled(rect, state, 1.0)
     }
 
-    /// Runs `F` inside a layout, using the provided position.
-    pub fn layout_at<F, T>(
+    pub fn text_input_scaled(
         &mut self,
-        top_left: Vec2,
-        direction: LayoutDirection,
-        spacing: u32,
-        with_bg: bool,
-        draw: F,
-    ) -> T
-    where
-        F: FnOnce(&mut Self) -> T,
-    {
-        // this layout should go wherever the current layout is
-        // @TODO izzy: add a "layout_at" fn
+        rect: Rect,
+        state: &mut TextInputState,
+        scale: f32,
+    ) -> Response {
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let active = self.is_active(id);
+        let mut focused = self.register_focusable(id, rect);
+        state.sanitize();
+
+        let text_origin = Vec2::new(rect.top_left.x + TEXT_INPUT_INSET, rect.top_left.y);
+        let inner_width = rect.size.x.saturating_sub(TEXT_INPUT_INSET * 2);
+        let caret_at_mouse = |ui: &Self, state: &TextInputState| {
+            let mouse_x =
+                (ui.content_mouse_position().x + state.scroll_x).saturating_sub(text_origin.x);
+            ui.nearest_char_boundary(&state.buffer, mouse_x, scale)
+        };
 
-        // ensure background is drawn first
-        let mut bg_idx = None;
-        if with_bg {
-            let idx = self.rect_raw(
-                Rect {
-                    top_left,
-                    size: Vec2::zero(), // temp
-                },
-                flags::NONE,
-                UIDrawRole::LayoutBackground,
-            );
-            bg_idx = Some(idx);
+        // pressing inside focuses and places the caret, pressing outside blurs.
+        // Enter and Space activating the field leave it be.
+        let mouse = !self.activated_by_key();
+        if mouse && self.input_state.activate_button == ButtonState::Down {
+            if hovered {
+                self.state.focused = Some(id);
+                focused = true;
+                let caret = caret_at_mouse(self, state);
+                state.set_caret(caret, false);
+                state.selection_anchor = Some(caret);
+            } else if focused {
+                self.state.focused = None;
+                focused = false;
+            }
+        } else if mouse && active && self.input_state.activate_button == ButtonState::Held {
+            // drag out a selection
+            let caret = caret_at_mouse(self, state);
+            state.set_caret(caret, true);
         }
 
-        // push a new layout based on the current layout position
-        self.layout_stack.push(Layout {
-            direction,
-            size: Vec2::zero(),
-            spacing,
-            top_left,
-        });
-        // do the draw, then pop the layout off and recompute the prev layout
-        let ret = draw(self);
-        let layout = self
-            .layout_stack
-            .pop()
-            .expect("layout: should have popped a layout");
-        self.recompute_current_layout(layout.size);
-
-        // update the background with the now-known size
-        if let Some(bg_idx) = bg_idx {
-            let draw_cmd = self
-                .command_buffer
-                .get_mut(bg_idx)
-                .expect("layout: expected command buffer idx to be valid");
-            match draw_cmd {
-                DrawCommand::DrawRect { draw_data } => {
-                    draw_data.rect.size = layout.size;
-                }
-                _ => unreachable!("layout: expected bg_idx to point to a rect draw"),
+        let mut changed = false;
+        if focused {
+            self.consume_move_keys(true, false);
+            let input = &self.input_state;
+            // shift extends the selection
+            let select = input.modifiers.shift;
+            if input.move_left == ButtonState::Down {
+                state.move_left(select);
+            }
+            if input.move_right == ButtonState::Down {
+                state.move_right(select);
+            }
+            if input.home_button == ButtonState::Down {
+                state.set_caret(0, select);
+            }
+            if input.end_button == ButtonState::Down {
+                state.set_caret(state.buffer.len(), select);
+            }
+            if input.backspace_button == ButtonState::Down {
+                changed |= state.delete_backward();
+            }
+            if input.delete_button == ButtonState::Down {
+                changed |= state.delete_forward();
+            }
+            // single line: control chars such as newlines are not inserted
+            let typed: String = input
+                .typed_chars
+                .iter()
+                .filter(|c| !c.is_control())
+                .collect();
+            if !typed.is_empty() {
+                state.insert_str(&typed);
+                changed = true;
             }
         }
 
-        ret
-    }
+        // keep the caret in view, and don't scroll past the end of the text
+        let caret_x = self.prefix_width(&state.buffer, state.caret, scale);
+        let text_size = self.font_info.compute_text_size(&state.buffer, scale);
+        if caret_x < state.scroll_x {
+            state.scroll_x = caret_x;
+        } else if caret_x > state.scroll_x + inner_width {
+            state.scroll_x = caret_x - inner_width;
+        }
+        state.scroll_x = state.scroll_x.min(text_size.x.saturating_sub(inner_width));
 
-    /// Draws a rectange the size of the current layout
-    pub fn layout_rect(&mut self) {
-        let layout = self.get_current_layout();
-        let rect: Rect = (*layout).into();
-        self.rect_raw(rect, flags::NONE, UIDrawRole::LayoutBackground);
-    }
+        let flags = self.interaction_flags(id, hovered, focused);
 
-    /// Finalize the computation of the UI and return the resulting state and draw info
-    pub fn end(mut self) -> UIResult {
-        // mouse/key down over hover/focus => active
-        if self.input_state.activate_button == ButtonState::Down {
-            let target_rect = self.hover_rect.or(self.state.focused);
-            if self.state.active_rect != target_rect {
-                self.state.active_drag_amt = 0.0;
-            }
-            self.state.active_rect = target_rect;
-        } else if self.input_state.activate_button == ButtonState::Held {
-            // maintain active rect
-        } else {
-            self.state.active_rect = None;
-            self.state.active_drag_amt = 0.0;
+        self.rect_raw(rect, flags, UIDrawRole::TextInputBackground);
+
+        // only the chars fully inside the field are drawn
+        let text_y = rect.top_left.y + rect.size.y.saturating_sub(text_size.y) / 2;
+        let scroll_x = state.scroll_x;
+        let visible: Vec<(usize, u32)> = (0..=state.buffer.len())
+            .filter(|idx| state.buffer.is_char_boundary(*idx))
+            .map(|idx| (idx, self.prefix_width(&state.buffer, idx, scale)))
+            .filter(|(_, x)| *x >= scroll_x && *x - scroll_x <= inner_width)
+            .collect();
+        let to_field_x = |x: u32| text_origin.x + x.saturating_sub(scroll_x).min(inner_width);
+
+        if let Some(selection) = state.selection() {
+            let start_x = to_field_x(self.prefix_width(&state.buffer, selection.start, scale));
+            let end_x = to_field_x(self.prefix_width(&state.buffer, selection.end, scale));
+            self.rect_raw(
+                Rect {
+                    top_left: Vec2::new(start_x, text_y),
+                    size: Vec2::new(end_x - start_x, text_size.y),
+                },
+                flags,
+                UIDrawRole::TextInputSelection,
+            );
         }
 
-        // figure out what the next thing to focus is
-        if self.input_state.focus_next_button == ButtonState::Released {
-            // if we had something focused, we find the next one
-            if let Some(prev_focus_rect) = self.state.focused {
-                let next_idx = self
-                    .focusables
-                    .iter()
-                    .copied()
-                    .position(|r| r == prev_focus_rect)
-                    .map(|p| p + 1)
-                    .unwrap_or_default();
-                let next_rect = self
-                    .focusables
-                    .get(next_idx)
-                    .or_else(|| self.focusables.first())
-                    .copied();
-                self.state.focused = next_rect;
-            } else {
-                self.state.focused = self.focusables.first().copied();
-            }
+        if let (Some(&(start, start_x)), Some(&(end, end_x))) = (visible.first(), visible.last()) {
+            self.text_raw(
+                state.buffer[start..end].to_string(),
+                Rect {
+                    top_left: Vec2::new(to_field_x(start_x), text_y),
+                    size: Vec2::new(end_x - start_x, text_size.y),
+                },
+                flags,
+                UIDrawRole::TextInputText,
+                scale,
+            );
         }
 
-        self.state.last_mouse_position = self.input_state.mouse_position;
-        UIResult {
-            new_state: self.state,
-            commands: self.command_buffer.into(),
+        if focused {
+            self.rect_raw(
+                Rect {
+                    top_left: Vec2::new(to_field_x(caret_x), text_y),
+                    size: Vec2::new(TEXT_INPUT_CARET_WIDTH, text_size.y),
+                },
+                flags,
+                UIDrawRole::TextInputCaret,
+            );
+        }
+
+        Response {
+            changed,
+            ..self.response(id, rect, hovered, focused)
         }
     }
-}
 
-#[cfg(test)]
+    /// Width of `text` up to the byte index `idx`
+    fn prefix_width(&self, text: &str, idx: usize, scale: f32) -> u32 {
+        self.font_info.compute_text_size(&text[..idx], scale).x
+    }
 
-mod test {
+    /// Byte index of the char boundary in `text` nearest to `x` pixels from its start
+    fn nearest_char_boundary(&self, text: &str, x: u32, scale: f32) -> usize {
+        (0..=text.len())
+            .filter(|idx| text.is_char_boundary(*idx))
+            .min_by_key(|idx| self.prefix_width(text, *idx, scale).abs_diff(x))
+            .unwrap_or_default()
+    }
 
-    const MOCK_TEXT_HEIGHT: u32 = 16;
-    const MOCK_TEXT_WIDTH: u32 = 8;
+    /// Draws a single line text field of `size` using the current layout position.
+    pub fn text_input_layout(&mut self, size: Vec2, state: &mut TextInputState) -> Response {
+        self.text_input_layout_scaled(size, state, 1.0)
+    }
 
-    use super::*;
-    fn mock_font_info() -> impl FontInformation {
-        struct MockFontInfo;
-        impl FontInformation for MockFontInfo {
-            fn compute_text_size(&self, text: &str, scale: f32) -> Vec2 {
-                let scale = scale.max(0.0);
-                Vec2 {
-                    x: (text.len() as f32 * MOCK_TEXT_WIDTH as f32 * scale).ceil() as u32,
-                    y: (MOCK_TEXT_HEIGHT as f32 * scale).ceil() as u32,
-                }
-            }
-        }
-        MockFontInfo
+    pub fn text_input_layout_scaled(
+        &mut self,
+        size: Vec2,
+        state: &mut TextInputState,
+        scale: f32,
+    ) -> Response {
+        let rect = self.layout_child_rect(size);
+        let response = self.text_input_scaled(rect, state, scale);
+        self.recompute_current_layout(size);
+        response
     }
 
-    #[test]
-    fn layout() {
-        const SECTION_TEXT_LEN: u32 = 9;
+    /// Draws a multi-line, wrapping text editor for the caller-held `state` inside `rect`.
+    /// Lines are scrolled vertically to keep the caret in view.
+    /// `changed` if the text changed.
+    pub fn text_area(&mut self, rect: Rect, state: &mut TextInputState) -> Response {
+        self.text_area_scaled(rect, state, 1.0)
+    }
 
-        let input_state = UIInputState {
-            activate_button: ButtonState::Up,
-            focus_next_button: ButtonState::Up,
-            mouse_position: Vec2::zero(),
-            ..Default::default()
+    pub fn text_area_scaled(
+        &mut self,
+        rect: Rect,
+        state: &mut TextInputState,
+        scale: f32,
+    ) -> Response {
+        let id = self.make_id();
+        let hovered = self.check_set_hover(id, rect);
+        let active = self.is_active(id);
+        let mut focused = self.register_focusable(id, rect);
+        state.sanitize();
+
+        let origin = Vec2::add(rect.top_left, Vec2::new(TEXT_INPUT_INSET, TEXT_INPUT_INSET));
+        let inner_size = Vec2::new(
+            rect.size.x.saturating_sub(TEXT_INPUT_INSET * 2),
+            rect.size.y.saturating_sub(TEXT_INPUT_INSET * 2),
+        );
+        let line_height = self.font_info.compute_text_size(" ", scale).y.max(1);
+        let page_lines = (inner_size.y / line_height).max(1) as usize;
+
+        let mut lines = self.wrap_text(&state.buffer, inner_size.x, scale);
+        let caret_at_mouse = |ui: &Self, state: &TextInputState, lines: &[Range<usize>]| {
+            let mouse = ui.content_mouse_position();
+            let y = (mouse.y + state.scroll_y).saturating_sub(origin.y);
+            let line = lines[((y / line_height) as usize).min(lines.len() - 1)].clone();
+            let x = mouse.x.saturating_sub(origin.x);
+            line.start + ui.nearest_char_boundary(&state.buffer[line], x, scale)
         };
 
-        let font_info = mock_font_info();
-        let ui_state = UIState::new();
-        let mut ctx = super::UIContext::new(ui_state, &font_info, input_state);
-        // draw a horizontal group of texts, each with a vertical layout of text inside
-        ctx.layout(LayoutDirection::Horizontal, Some(4), false, |ctx| {
-            let main_layout = *ctx.get_current_layout();
-            for i in 0..3 {
-                let label = format!("Section {}", i);
-                assert!(
-                    label.len() as u32 == SECTION_TEXT_LEN,
-                    "broken test assertion"
+        // pressing inside focuses and places the caret, pressing outside blurs.
+        // Enter and Space activating the field leave it be.
+        let mouse = !self.activated_by_key();
+        if mouse && self.input_state.activate_button == ButtonState::Down {
+            if hovered {
+                self.state.focused = Some(id);
+                focused = true;
+                let caret = caret_at_mouse(self, state, &lines);
+                state.set_caret(caret, false);
+                state.selection_anchor = Some(caret);
+            } else if focused {
+                self.state.focused = None;
+                focused = false;
+            }
+        } else if mouse && active && self.input_state.activate_button == ButtonState::Held {
+            // drag out a selection
+            let caret = caret_at_mouse(self, state, &lines);
+            state.set_caret(caret, true);
+        }
+
+        let mut changed = false;
+        if focused {
+            self.consume_move_keys(true, true);
+            let input = &self.input_state;
+            let line = &lines[line_of(&lines, state.caret)];
+            let (line_start, line_end) = (line.start, line.end);
+            // shift extends the selection
+            let select = input.modifiers.shift;
+            let mut line_moves = 0_isize;
+            if input.move_up == ButtonState::Down {
+                line_moves -= 1;
+            }
+            if input.move_down == ButtonState::Down {
+                line_moves += 1;
+            }
+            if input.page_up_button == ButtonState::Down {
+                line_moves -= page_lines as isize;
+            }
+            if input.page_down_button == ButtonState::Down {
+                line_moves += page_lines as isize;
+            }
+            if line_moves != 0 {
+                let caret =
+                    self.caret_moved_lines(&state.buffer, &lines, state.caret, line_moves, scale);
+                state.set_caret(caret, select);
+            }
+
+            let input = &self.input_state;
+            if input.move_left == ButtonState::Down {
+                state.move_left(select);
+            }
+            if input.move_right == ButtonState::Down {
+                state.move_right(select);
+            }
+            // home and end work on the visual line
+            if input.home_button == ButtonState::Down {
+                state.set_caret(line_start, select);
+            }
+            if input.end_button == ButtonState::Down {
+                state.set_caret(line_end, select);
+            }
+            if input.backspace_button == ButtonState::Down {
+                changed |= state.delete_backward();
+            }
+            if input.delete_button == ButtonState::Down {
+                changed |= state.delete_forward();
+            }
+            let typed: String = input
+                .typed_chars
+                .iter()
+                .map(|c| if *c == '\r' { '\n' } else { *c })
+                .filter(|c| *c == '\n' || !c.is_control())
+                .collect();
+            if !typed.is_empty() {
+                state.insert_str(&typed);
+                changed = true;
+            }
+        }
+
+        if changed {
+            lines = self.wrap_text(&state.buffer, inner_size.x, scale);
+        }
+
+        // scroll by whole lines, keeping the caret line in view
+        let caret_line = line_of(&lines, state.caret);
+        let mut first_line = (state.scroll_y / line_height) as usize;
+        if caret_line < first_line {
+            first_line = caret_line;
+        } else if caret_line >= first_line + page_lines {
+            first_line = caret_line + 1 - page_lines;
+        }
+        first_line = first_line.min(lines.len().saturating_sub(page_lines));
+        state.scroll_y = first_line as u32 * line_height;
+
+        let flags = self.interaction_flags(id, hovered, focused);
+
+        self.rect_raw(rect, flags, UIDrawRole::TextInputBackground);
+
+        let selection = state.selection();
+        let visible_lines = first_line..lines.len().min(first_line + page_lines);
+        for (row, line) in lines[visible_lines.clone()].iter().enumerate() {
+            let line_y = origin.y + row as u32 * line_height;
+            let line_text = &state.buffer[line.clone()];
+
+            if let Some(selection) = &selection {
+                let start = selection.start.max(line.start);
+                let end = selection.end.min(line.end);
+                if start < end {
+                    let start_x = self.prefix_width(line_text, start - line.start, scale);
+                    let end_x = self.prefix_width(line_text, end - line.start, scale);
+                    self.rect_raw(
+                        Rect {
+                            top_left: Vec2::new(origin.x + start_x, line_y),
+                            size: Vec2::new(end_x - start_x, line_height),
+                        },
+                        flags,
+                        UIDrawRole::TextInputSelection,
+                    );
+                }
+            }
+
+            if !line_text.is_empty() {
+                let text_size = self.font_info.compute_text_size(line_text, scale);
+                self.text_raw(
+                    line_text.to_string(),
+                    Rect {
+                        top_left: Vec2::new(origin.x, line_y),
+                        size: Vec2::new(text_size.x, line_height),
+                    },
+                    flags,
+                    UIDrawRole::TextInputText,
+                    scale,
                 );
+            }
+        }
 
-                ctx.text_layout(label);
+        if focused && visible_lines.contains(&caret_line) {
+            let line = &lines[caret_line];
+            let caret_x =
+                self.prefix_width(&state.buffer[line.clone()], state.caret - line.start, scale);
+            self.rect_raw(
+                Rect {
+                    top_left: Vec2::new(
+                        origin.x + caret_x,
+                        origin.y + (caret_line - first_line) as u32 * line_height,
+                    ),
+                    size: Vec2::new(TEXT_INPUT_CARET_WIDTH, line_height),
+                },
+                flags,
+                UIDrawRole::TextInputCaret,
+            );
+        }
 
-                for j in 0..2 {
-                    let sub_label = format!("Section {} item {}", i, j);
+        Response {
+            changed,
+            ..self.response(id, rect, hovered, focused)
+        }
+    }
 
-                    ctx.layout(LayoutDirection::Vertical, Some(2), false, |ctx| {
-                        ctx.text_layout(sub_label);
+    /// Draws a multi-line text editor of `size` using the current layout position.
+    pub fn text_area_layout(&mut self, size: Vec2, state: &mut TextInputState) -> Response {
+        self.text_area_layout_scaled(size, state, 1.0)
+    }
 
-                        let sub_layout = ctx.get_current_layout();
-                        assert_eq!(
-                            sub_layout.top_left.y,
-                            main_layout.top_left.y + MOCK_TEXT_HEIGHT + sub_layout.spacing
-                        );
-                    });
+    pub fn text_area_layout_scaled(
+        &mut self,
+        size: Vec2,
+        state: &mut TextInputState,
+        scale: f32,
+    ) -> Response {
+        let rect = self.layout_child_rect(size);
+        let response = self.text_area_scaled(rect, state, scale);
+        self.recompute_current_layout(size);
+        response
+    }
+
+    /// Splits `text` into byte ranges of visual lines no wider than `width`.
+    /// Lines break at newlines, and after whitespace where possible when too wide.
+    fn wrap_text(&self, text: &str, width: u32, scale: f32) -> Vec<Range<usize>> {
+        let mut lines = vec![];
+        let mut line_start = 0;
+        for hard_line in text.split('\n') {
+            let line_end = line_start + hard_line.len();
+            let mut start = line_start;
+            while self
+                .font_info
+                .compute_text_size(&text[start..line_end], scale)
+                .x
+                > width
+            {
+                // find the longest prefix that fits, remembering the last whitespace break
+                let mut fits = start;
+                let mut last_break = None;
+                for (i, c) in text[start..line_end].char_indices() {
+                    let next = start + i + c.len_utf8();
+                    if self
+                        .font_info
+                        .compute_text_size(&text[start..next], scale)
+                        .x
+                        > width
+                    {
+                        break;
+                    }
+                    fits = next;
+                    if c.is_whitespace() {
+                        last_break = Some(next);
+                    }
                 }
+                // always take at least one char so wrapping makes progress
+                let end = match last_break {
+                    Some(end) => end,
+                    None if fits == start => next_char_boundary(text, start),
+                    None => fits,
+                };
+                lines.push(start..end);
+                start = end;
             }
-        });
+            lines.push(start..line_end);
+            line_start = line_end + 1;
+        }
+        lines
+    }
 
-        println!("layout {:?}", ctx.get_current_layout());
+    /// Moves `caret` by `delta` visual lines, keeping its horizontal position.
+    /// Moving past the first or last line goes to the start or end of the text.
+    fn caret_moved_lines(
+        &self,
+        text: &str,
+        lines: &[Range<usize>],
+        caret: usize,
+        delta: isize,
+        scale: f32,
+    ) -> usize {
+        let line = line_of(lines, caret);
+        let target = line as isize + delta;
+        if target < 0 {
+            return 0;
+        }
+        if target as usize >= lines.len() {
+            return text.len();
+        }
+        let x = self.prefix_width(&text[lines[line].clone()], caret - lines[line].start, scale);
+        let target = lines[target as usize].clone();
+        target.start + self.nearest_char_boundary(&text[target], x, scale)
+    }
 
-        assert_eq!(ctx.command_buffer.len(), 9);
+    /// Runs `F` inside a layout, using the current layout.
+    /// If `spacing` is `None` it will use the current layout spacing.
+    pub fn layout<F, T>(
+        &mut self,
+        direction: LayoutDirection,
+        spacing: Option<u32>,
+        with_bg: bool,
+        draw: F,
+    ) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let spacing = spacing.unwrap_or(self.get_current_layout().spacing);
+        let background = with_bg.then_some((UIDrawRole::LayoutBackground, flags::NONE));
+        self.layout_with_background(None, direction, spacing, background, draw)
     }
 
-    #[test]
-    fn nested_layout_size_propagates() {
-        let font_info = mock_font_info();
-        let ui_state = UIState::new();
-        let mut ctx = super::UIContext::new(ui_state, &font_info, UIInputState::default());
+    /// Runs `F` inside a layout, using the provided position.
+    pub fn layout_at<F, T>(
+        &mut self,
+        top_left: Vec2,
+        direction: LayoutDirection,
+        spacing: u32,
+        with_bg: bool,
+        draw: F,
+    ) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let background = with_bg.then_some((UIDrawRole::LayoutBackground, flags::NONE));
+        self.layout_with_background(Some(top_left), direction, spacing, background, draw)
+    }
 
-        ctx.layout(LayoutDirection::Horizontal, Some(4), false, |ctx| {
-            let parent_before = *ctx.get_current_layout();
-            let child_layout = ctx.layout(LayoutDirection::Vertical, Some(3), false, |ctx| {
-                ctx.text_layout("Hi".into());
-                ctx.text_layout("WiderText".into());
-                *ctx.get_current_layout()
-            });
-            assert_eq!(child_layout.size.x, MOCK_TEXT_WIDTH * 9);
-            assert_eq!(child_layout.size.y, MOCK_TEXT_HEIGHT * 2 + 3);
+    /// Runs `F` inside a grid placed in the current layout, putting each widget or layout drawn
+    /// in the next cell, left to right across `columns` and then down a row.
+    /// `spacing` is between columns in `x` and rows in `y`. Cells are aligned in their row by
+    /// the layout style's `align`, and lengths are taken from the width of the cell.
+    /// Like alignment, columns and rows settle the frame after their cells change.
+    pub fn grid<F, T>(&mut self, columns: &[GridColumn], spacing: Vec2, with_bg: bool, draw: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        assert!(!columns.is_empty(), "grid: should have at least one column");
+        self.next_grid = Some((columns.to_vec(), spacing));
+        let background = with_bg.then_some((UIDrawRole::LayoutBackground, flags::NONE));
+        self.layout_with_background(
+            None,
+            LayoutDirection::Horizontal,
+            spacing.x,
+            background,
+            draw,
+        )
+    }
+
+    /// Runs `F` inside a flow placed in the current layout, putting each widget or layout drawn
+    /// after the last, left to right, and starting a row under them when the next would make the
+    /// row wider than `max_width`, padding included. A child wider than `max_width` takes a row alone.
+    /// `spacing` is between children in `x` and rows in `y`. Children are aligned to the top of
+    /// their row, and fills take the rest of it.
+    pub fn flow<F, T>(&mut self, max_width: u32, spacing: Vec2, with_bg: bool, draw: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        self.next_flow = Some((max_width, spacing.y));
+        let background = with_bg.then_some((UIDrawRole::LayoutBackground, flags::NONE));
+        self.layout_with_background(
+            None,
+            LayoutDirection::Horizontal,
+            spacing.x,
+            background,
+            draw,
+        )
+    }
 
-            let parent_after = *ctx.get_current_layout();
+    /// Runs `F` inside a layout at `top_left`, or placed in the current layout if `None`,
+    /// drawing a background of the given role and flags behind it.
+    fn layout_with_background<F, T>(
+        &mut self,
+        top_left: Option<Vec2>,
+        direction: LayoutDirection,
+        spacing: u32,
+        background: Option<(UIDrawRole, Flags)>,
+        draw: F,
+    ) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let id = self.make_id();
+        let mut style = self.next_layout_style.take().unwrap_or_default();
+        let last = self.state.measures.get(&id).copied();
+
+        // placed as big as it was last frame before stretching, filling the parent if it stretches
+        // and fixed to the length the parent gives it
+        let parent = self.get_current_layout();
+        let placed = parent.place(
+            last.map_or(Vec2::zero(), |last| last.natural),
+            &self.next_child,
+        );
+        let pd = parent.direction;
+        let stretched = match parent.style.align {
+            Align::Stretch if top_left.is_none() => pd.vec(0, pd.cross(placed.size)),
+            _ => Vec2::zero(),
+        };
+        if top_left.is_none() && parent.sizes(&self.next_child) {
+            match pd {
+                LayoutDirection::Vertical => style.height = Some(placed.size.y),
+                LayoutDirection::Horizontal => style.width = Some(placed.size.x),
+            }
+        }
+        let top_left = top_left.unwrap_or(placed.top_left);
+        let child = std::mem::take(&mut self.next_child);
 
-            assert_eq!(
-                parent_after.top_left.x,
-                parent_before.top_left.x + child_layout.size.x + parent_after.spacing
+        // ensure background is drawn first
+        let mut bg_idx = None;
+        if let Some((role, flags)) = background {
+            let idx = self.rect_raw(
+                Rect {
+                    top_left,
+                    size: Vec2::zero(), // temp
+                },
+                flags,
+                role,
             );
-            assert_eq!(parent_after.size.x, child_layout.size.x);
-            assert_eq!(parent_after.size.y, child_layout.size.y);
-        });
+            bg_idx = Some(idx);
+        }
+
+        // push a new layout based on the current layout position
+        let mut layout = Layout::arranged(id, direction, spacing, top_left, style, stretched, last);
+        if let Some((columns, spacing)) = self.next_grid.take() {
+            let last = self.state.grids.get(&id).cloned().unwrap_or_default();
+            let grid = Grid::new(columns, spacing, layout.inner.x, last);
+            layout = layout.with_arrangement(Arrangement::Grid(grid));
+        } else if let Some((width, row_spacing)) = self.next_flow.take() {
+            let width = width.saturating_sub(layout.style.padding.size().x);
+            layout = layout.with_arrangement(Arrangement::Flow(Flow::new(width, row_spacing)));
+        }
+        self.layout_stack.push(layout);
+        // children are identified relative to the layout
+        self.id_stack.push(IdScope::new(id));
+        let hits_start = self.hits.len();
+
+        // do the draw, then pop the layout off and recompute the prev layout
+        let ret = draw(self);
+        self.id_stack.pop();
+        let layout = self
+            .layout_stack
+            .pop()
+            .expect("layout: should have popped a layout");
+        self.next_child = child;
+        self.recompute_current_layout(layout.natural);
+        self.measure_layout(&layout);
+
+        // update the background with the now-known size
+        if let Some(bg_idx) = bg_idx {
+            // backgrounds are panels covering what's under them, but not their children
+            let rect = Rect {
+                top_left,
+                size: layout.size,
+            };
+            if self.mouse_in_clip() && rect.contains(self.content_mouse_position()) {
+                self.hits.insert(hits_start, (id, self.layer));
+            }
+
+            let draw_cmd = self
+                .command_buffer
+                .get_mut(bg_idx)
+                .expect("layout: expected command buffer idx to be valid");
+            match draw_cmd {
+                DrawCommand::DrawRect { draw_data } => {
+                    draw_data.rect.size = layout.size;
+                }
+                _ => unreachable!("layout: expected bg_idx to point to a rect draw"),
+            }
+        }
+
+        ret
+    }
+
+    /// Draws a rectange the size of the current layout
+    pub fn layout_rect(&mut self) {
+        let layout = self.get_current_layout();
+        let rect: Rect = layout.clone().into();
+        self.rect_raw(rect, flags::NONE, UIDrawRole::LayoutBackground);
+    }
+
+    /// Runs `draw` inside a vertical layout in `rect`, clipping and scrolling its contents.
+    /// Scrollbars are shown along the right and bottom edges when the content overflows.
+    pub fn scroll_area<F, T>(&mut self, rect: Rect, state: &mut ScrollState, draw: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let id = self.make_id();
+        let hits_start = self.hits.len();
+        self.check_set_hover(id, rect);
+
+        // scrollbars take space from the view if last frame's content didn't fit
+        let overflows = |view: Vec2| (state.content_size.x > view.x, state.content_size.y > view.y);
+        let (mut show_x, mut show_y) = overflows(rect.size);
+        if show_x || show_y {
+            let bars = Vec2::new(SCROLLBAR_SIZE, SCROLLBAR_SIZE);
+            (show_x, show_y) = overflows(Vec2::new(
+                rect.size.x.saturating_sub(bars.x * show_y as u32),
+                rect.size.y.saturating_sub(bars.y * show_x as u32),
+            ));
+        }
+        let view = Rect {
+            top_left: rect.top_left,
+            size: Vec2::new(
+                rect.size.x.saturating_sub(SCROLLBAR_SIZE * show_y as u32),
+                rect.size.y.saturating_sub(SCROLLBAR_SIZE * show_x as u32),
+            ),
+        };
+        let max_offset = Vec2::new(
+            state.content_size.x.saturating_sub(view.size.x),
+            state.content_size.y.saturating_sub(view.size.y),
+        );
+
+        if show_y {
+            let track = Rect {
+                top_left: Vec2::new(view.top_left.x + view.size.x, view.top_left.y),
+                size: Vec2::new(SCROLLBAR_SIZE, view.size.y),
+            };
+            let view_len = view.size.y;
+            self.scrollbar(
+                id.with("y"),
+                track,
+                true,
+                state.content_size.y,
+                view_len,
+                &mut state.offset.y,
+            );
+        }
+        if show_x {
+            let track = Rect {
+                top_left: Vec2::new(view.top_left.x, view.top_left.y + view.size.y),
+                size: Vec2::new(view.size.x, SCROLLBAR_SIZE),
+            };
+            let view_len = view.size.x;
+            self.scrollbar(
+                id.with("x"),
+                track,
+                false,
+                state.content_size.x,
+                view_len,
+                &mut state.offset.x,
+            );
+        }
+        state.offset = Vec2::new(
+            state.offset.x.min(max_offset.x),
+            state.offset.y.min(max_offset.y),
+        );
+
+        // children are drawn in the scrolled space, clipped to the view
+        self.push_clip_scrolled(view, state.offset);
+        let focused_outside = self.focused_seen.take();
+
+        let spacing = self.get_current_layout().spacing;
+        self.layout_stack.push(Layout {
+            id: id.with("content"),
+            ..Layout::new(
+                LayoutDirection::Vertical,
+                spacing,
+                view.top_left,
+                Vec2::zero(),
+            )
+        });
+        self.id_stack.push(IdScope::new(id));
+        let ret = draw(self);
+        self.id_stack.pop();
+        let layout = self
+            .layout_stack
+            .pop()
+            .expect("scroll area: should have popped a layout");
+        state.content_size = layout.size;
+        self.measure_layout(&layout);
+
+        // children had the first chance to use the wheel.
+        // the area or one of its children must be hovered, not covered by something else
+        let hovered = !self.disabled
+            && self.hits[hits_start..]
+                .iter()
+                .any(|(hit, _)| self.state.hovered == Some(*hit));
+        let (wheel_x, wheel_y) = self.wheel_delta();
+        if hovered && (wheel_x != 0.0 && max_offset.x > 0 || wheel_y != 0.0 && max_offset.y > 0) {
+            let scrolled = |offset: u32, wheel: f32, max: u32| {
+                (offset as f32 - wheel * WHEEL_SCROLL_STEP).clamp(0.0, max as f32) as u32
+            };
+            state.offset = Vec2::new(
+                scrolled(state.offset.x, wheel_x, max_offset.x),
+                scrolled(state.offset.y, wheel_y, max_offset.y),
+            );
+            self.consume_wheel();
+        }
+
+        // scroll a newly focused child into view, and let any parent scroll to this area
+        let depth = self.clip_stack.len();
+        self.pop_clip();
+        match self.focused_seen {
+            Some((focused_depth, focused_rect)) if focused_depth == depth => {
+                if self.state.focus_moved {
+                    // the focused child may start left of or above the view
+                    let span = |start: u32, view_start: u32, len: u32| {
+                        let start = i64::from(start) - i64::from(view_start);
+                        (start, start + i64::from(len))
+                    };
+                    let (left, right) = span(
+                        focused_rect.top_left.x,
+                        view.top_left.x,
+                        focused_rect.size.x,
+                    );
+                    let (top, bottom) = span(
+                        focused_rect.top_left.y,
+                        view.top_left.y,
+                        focused_rect.size.y,
+                    );
+                    let max_offset = Vec2::new(
+                        state.content_size.x.saturating_sub(view.size.x),
+                        state.content_size.y.saturating_sub(view.size.y),
+                    );
+                    state.offset = Vec2::new(
+                        scrolled_into_view(state.offset.x, view.size.x, left, right, max_offset.x),
+                        scrolled_into_view(state.offset.y, view.size.y, top, bottom, max_offset.y),
+                    );
+                }
+                self.focused_seen = Some((depth - 1, view));
+            }
+            Some(_) => {}
+            None => self.focused_seen = focused_outside,
+        }
+
+        ret
+    }
+
+    /// Clips all following draws and mouse interaction to `rect` until the matching `pop_clip`.
+    /// Nested clips are intersected with their parents.
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.push_clip_scrolled(rect, Vec2::zero());
+    }
+
+    /// Pushes a clip whose contents are scrolled by `offset`.
+    fn push_clip_scrolled(&mut self, rect: Rect, offset: Vec2) {
+        self.command_buffer
+            .push_back(DrawCommand::PushClip { rect, offset });
+        let screen_rect = self.to_screen(rect);
+        let clip = ClipRegion {
+            rect: self
+                .clip_stack
+                .last()
+                .map_or(screen_rect, |parent| parent.rect.intersect(screen_rect)),
+            offset: Vec2::add(self.scroll_offset(), offset),
+        };
+        self.clip_stack.push(clip);
+    }
+
+    /// Pops the clip pushed by `push_clip`.
+    pub fn pop_clip(&mut self) {
+        self.clip_stack
+            .pop()
+            .expect("pop clip: should have a clip to pop");
+        self.command_buffer.push_back(DrawCommand::PopClip);
+    }
+
+    /// Executes `func` with draws and mouse interaction clipped to `rect` for the duration of the call.
+    pub fn with_clip<F, T>(&mut self, rect: Rect, func: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        self.push_clip(rect);
+        let ret = func(self);
+        self.pop_clip();
+        ret
+    }
+
+    /// The area of the screen that draws are currently clipped to, if any
+    pub fn clip_rect(&self) -> Option<Rect> {
+        self.clip_stack.last().map(|clip| clip.rect)
+    }
+
+    /// Draws a scroll area of `size` using the current layout position.
+    pub fn scroll_area_layout<F, T>(&mut self, size: Vec2, state: &mut ScrollState, draw: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let rect = self.layout_child_rect(size);
+        let ret = self.scroll_area(rect, state, draw);
+        self.recompute_current_layout(size);
+        ret
+    }
+
+    /// Handles dragging and draws one scrollbar of a scroll area along `track`.
+    /// `offset` is how far content of `content_len` is scrolled within a view of `view_len`.
+    fn scrollbar(
+        &mut self,
+        id: WidgetId,
+        track: Rect,
+        vertical: bool,
+        content_len: u32,
+        view_len: u32,
+        offset: &mut u32,
+    ) {
+        let along = |v: Vec2| if vertical { v.y } else { v.x };
+        let track_len = along(track.size);
+        let max_offset = content_len.saturating_sub(view_len);
+        let thumb_len = (track_len as u64 * view_len as u64 / content_len.max(1) as u64) as u32;
+        let thumb_len = thumb_len.max(SCROLLBAR_MIN_THUMB).min(track_len);
+        let free_len = track_len - thumb_len;
+
+        // dragging anywhere on the track moves the thumb with the mouse
+        let hovered = self.check_set_hover(id, track);
+        let active = self.is_active(id);
+        if active && max_offset > 0 && free_len > 0 {
+            let delta = along(self.input_state.mouse_position) as f32
+                - along(self.state.last_mouse_position) as f32;
+            self.state.active_drag_amt += delta * max_offset as f32 / free_len as f32;
+            let scrolled = self.state.active_drag_amt.trunc();
+            *offset = (*offset as f32 + scrolled).clamp(0.0, max_offset as f32) as u32;
+            self.state.active_drag_amt -= scrolled;
+        }
+
+        let thumb_pos = if max_offset == 0 {
+            0
+        } else {
+            (free_len as u64 * (*offset).min(max_offset) as u64 / max_offset as u64) as u32
+        };
+        let thumb = if vertical {
+            Rect {
+                top_left: Vec2::add(track.top_left, Vec2::new(0, thumb_pos)),
+                size: Vec2::new(track.size.x, thumb_len),
+            }
+        } else {
+            Rect {
+                top_left: Vec2::add(track.top_left, Vec2::new(thumb_pos, 0)),
+                size: Vec2::new(thumb_len, track.size.y),
+            }
+        };
+
+        let flags = self.interaction_flags(id, hovered, false);
+        self.rect_raw(track, flags, UIDrawRole::ScrollbarTrack);
+        self.rect_raw(thumb, flags, UIDrawRole::ScrollbarThumb);
+    }
+
+    /// Finds the focusable widget nearest the focused one in the direction of a move key
+    /// no widget used. Widgets sharing a row or column with the focused one are preferred,
+    /// then the closest, then the first registered.
+    fn directional_focus_target(&self) -> Option<WidgetId> {
+        let input = &self.input_state;
+        let (horizontal_used, vertical_used) = self.moves_consumed;
+        let pressed = |button: ButtonState, used: bool| !used && button == ButtonState::Down;
+        let direction = if pressed(input.move_left, horizontal_used) {
+            (-1, 0)
+        } else if pressed(input.move_right, horizontal_used) {
+            (1, 0)
+        } else if pressed(input.move_up, vertical_used) {
+            (0, -1)
+        } else if pressed(input.move_down, vertical_used) {
+            (0, 1)
+        } else {
+            return None;
+        };
+
+        let Some((focused, from)) = self
+            .state
+            .focused
+            .and_then(|focused| self.focusables.iter().find(|(id, ..)| *id == focused))
+            .map(|(id, rect, _)| (*id, *rect))
+        else {
+            // nothing focused yet, so start at the first widget
+            return self.focusables.first().map(|(id, ..)| *id);
+        };
+
+        // a rect's extent along the direction of movement, flipped so it always moves forwards,
+        // and its extent across it
+        let spans = |rect: &Rect| {
+            let (x0, y0) = (rect.top_left.x as i64, rect.top_left.y as i64);
+            let (x1, y1) = (x0 + rect.size.x as i64, y0 + rect.size.y as i64);
+            match direction {
+                (1, 0) => ((x0, x1), (y0, y1)),
+                (-1, 0) => ((-x1, -x0), (y0, y1)),
+                (0, 1) => ((y0, y1), (x0, x1)),
+                _ => ((-y1, -y0), (x0, x1)),
+            }
+        };
+        // doubled, to stay in integers
+        let center = |(start, end): (i64, i64)| start + end;
+        let gap = |a: (i64, i64), b: (i64, i64)| (b.0 - a.1).max(a.0 - b.1).max(0);
+        let overlaps = |a: (i64, i64), b: (i64, i64)| a.0 < b.1 && b.0 < a.1;
+
+        let (from_along, from_across) = spans(&from);
+        let candidates = self
+            .focusables
+            .iter()
+            .enumerate()
+            .filter(|(_, (id, ..))| *id != focused)
+            .map(|(idx, (id, rect, _))| {
+                let (along, across) = spans(rect);
+                (idx, *id, along, across)
+            });
+        let offset_across = |across: (i64, i64)| (center(across) - center(from_across)).abs();
+
+        let ahead = candidates
+            .clone()
+            .filter(|(_, _, along, _)| center(*along) > center(from_along))
+            .min_by_key(|(idx, _, along, across)| {
+                let distance = (along.0 - from_along.1).max(0);
+                (
+                    distance + 2 * gap(from_across, *across),
+                    offset_across(*across),
+                    *idx,
+                )
+            });
+        if let Some((_, id, _, _)) = ahead {
+            return Some(id);
+        }
+
+        // wrap around to the far end of the same row or column
+        if !self.focus_wrap {
+            return None;
+        }
+        candidates
+            .filter(|(_, _, along, across)| {
+                overlaps(from_across, *across) && center(*along) < center(from_along)
+            })
+            .min_by_key(|(idx, _, along, across)| (along.0, offset_across(*across), *idx))
+            .map(|(_, id, _, _)| id)
+    }
+
+    /// Opens the focus scopes drawn for the first time this frame and closes those no longer drawn.
+    fn update_focus_scopes(&mut self) {
+        let drawn = std::mem::take(&mut self.focus_scopes_drawn);
+        let mut open = std::mem::take(&mut self.state.focus_scopes);
+
+        // closed scopes give focus back if it was left inside them, innermost first
+        for scope in open.iter().rev() {
+            let closed = !drawn.iter().any(|d| d.id == scope.id);
+            let focus_drawn = self
+                .state
+                .focused
+                .is_some_and(|focused| self.focusables.iter().any(|(id, ..)| *id == focused));
+            if closed && !focus_drawn {
+                self.state.focused = scope.restore;
+            }
+        }
+        open.retain(|scope| drawn.iter().any(|d| d.id == scope.id));
+
+        // new scopes remember the focus, and trapping ones take it
+        for scope in drawn {
+            if let Some(existing) = open.iter_mut().find(|s| s.id == scope.id) {
+                existing.trap = scope.trap;
+                continue;
+            }
+            open.push(FocusScope {
+                restore: self.state.focused,
+                ..scope
+            });
+            let mut inside = self
+                .focusables
+                .iter()
+                .filter(|(.., trap)| *trap == Some(scope.id))
+                .map(|(id, ..)| *id);
+            if scope.trap
+                && !self
+                    .state
+                    .focused
+                    .is_some_and(|focused| inside.clone().any(|id| id == focused))
+            {
+                self.state.focused = inside.next();
+            }
+        }
+        self.state.focus_scopes = open;
+    }
+
+    /// Finalize the computation of the UI and return the resulting state and draw info
+    pub fn end(mut self) -> UIResult {
+        debug_assert!(
+            self.clip_stack.is_empty(),
+            "end: every push_clip should be popped"
+        );
+
+        // the topmost layer takes hover, then the last drawn widget
+        let hover = self
+            .hits
+            .iter()
+            .max_by_key(|(_, layer)| *layer)
+            .map(|(id, _)| *id);
+
+        // mouse/key down over hover/focus => active, activation keys only activating focus
+        if self.input_state.activate_button == ButtonState::Down {
+            let target = if self.input_state.activation_key_pressed() {
+                self.state.focused
+            } else {
+                hover.or(self.state.focused)
+            };
+            if self.state.active != target {
+                self.state.active_drag_amt = 0.0;
+            }
+            self.state.active = target;
+        } else if self.input_state.activate_button == ButtonState::Held {
+            // maintain active widget
+        } else {
+            self.state.active = None;
+            self.state.active_drag_amt = 0.0;
+        }
+        self.state.dragged = self
+            .state
+            .active
+            .filter(|_| self.input_state.activate_button == ButtonState::Held);
+        for event in &self.input_state.key_events {
+            if matches!(event.key, Key::Enter | Key::Space) {
+                self.state.activate_key_held = event.pressed;
+            }
+        }
+
+        self.update_focus_scopes();
+        // an open trapping scope confines traversal to its widgets
+        let trap = self
+            .state
+            .focus_scopes
+            .iter()
+            .rev()
+            .find(|scope| scope.trap)
+            .map(|scope| scope.id);
+        if trap.is_some() {
+            self.focusables.retain(|(.., scope)| *scope == trap);
+        }
+        self.resolve_shortcuts(trap);
+
+        // arrow keys move focus and selection through a focused radio's group
+        self.state.radio_selected = None;
+        if let Some((group, focused)) = self
+            .radios
+            .iter()
+            .copied()
+            .find(|(_, id)| self.state.focused == Some(*id))
+        {
+            let input = &self.input_state;
+            let forward = [input.move_down, input.move_right].contains(&ButtonState::Down);
+            let backward = [input.move_up, input.move_left].contains(&ButtonState::Down);
+            if forward != backward {
+                let members: Vec<WidgetId> = self
+                    .radios
+                    .iter()
+                    .filter(|(g, _)| *g == group)
+                    .map(|(_, id)| *id)
+                    .collect();
+                let idx = members
+                    .iter()
+                    .position(|id| *id == focused)
+                    .unwrap_or_default();
+                let next = if forward {
+                    (idx + 1) % members.len()
+                } else {
+                    (idx + members.len() - 1) % members.len()
+                };
+                // with directional focus, arrows leave the group at its ends instead of wrapping
+                let wraps = if forward { next < idx } else { next > idx };
+                if !(wraps && self.directional_focus) {
+                    self.state.focused = Some(members[next]);
+                    self.state.radio_selected = Some(members[next]);
+                    self.consume_move_keys(true, true);
+                }
+            }
+        }
+
+        if self.directional_focus
+            && let Some(target) = self.directional_focus_target()
+        {
+            self.state.focused = Some(target);
+        }
+
+        // figure out what the next thing to focus is
+        let forward = self.input_state.focus_next_button == ButtonState::Released;
+        let backward = self.input_state.focus_prev_button == ButtonState::Released;
+        if forward != backward {
+            let last = self.focusables.len().saturating_sub(1);
+            // if we had something focused, we find the one after or before it
+            let current = self
+                .state
+                .focused
+                .and_then(|focused| self.focusables.iter().position(|(id, ..)| *id == focused));
+            let next = match current {
+                None if forward => 0,
+                None => last,
+                Some(idx) if forward && idx < last => idx + 1,
+                Some(idx) if backward && idx > 0 => idx - 1,
+                // at an end
+                Some(idx) if !self.focus_wrap => idx,
+                Some(_) if forward => 0,
+                Some(_) => last,
+            };
+            self.state.focused = self.focusables.get(next).map(|(id, ..)| *id);
+        }
+
+        // focus that escaped the trap, e.g. by clicking behind it, goes back inside
+        let in_focusables = |ui: &Self, id: WidgetId| ui.focusables.iter().any(|(f, ..)| *f == id);
+        if trap.is_some()
+            && self
+                .state
+                .focused
+                .is_some_and(|focused| !in_focusables(&self, focused))
+        {
+            self.state.focused = self
+                .focus_at_start
+                .filter(|id| in_focusables(&self, *id))
+                .or_else(|| self.focusables.first().map(|(id, ..)| *id));
+        }
+
+        // cancel clears focus, unless it closed something
+        if self.input_state.cancel_button == ButtonState::Down && !self.cancel_consumed {
+            self.state.focused = None;
+        }
+
+        if let Some(request) = self.focus_request {
+            self.state.focused = request;
+        }
+
+        // popups close when their combo box loses focus or isn't drawn
+        if self
+            .state
+            .open_popup
+            .is_some_and(|id| !self.popup_drawn || self.state.focused != Some(id))
+        {
+            self.state.open_popup = None;
+        }
+        // as do context menus whose widget isn't drawn
+        if !self.context_menu_drawn {
+            self.state.context_menu = None;
+        }
+
+        self.state.focus_moved = self.state.focused != self.focus_at_start;
+        self.state.last_focused = self.focus_at_start;
+
+        // remember where the focused widget was drawn
+        if let Some(focused) = self.state.focused {
+            self.state.focused_rect = self
+                .focusables
+                .iter()
+                .find(|(id, ..)| *id == focused)
+                .map(|(_, rect, _)| *rect);
+        } else {
+            self.state.focused_rect = None;
+        }
+
+        self.state.last_mouse_position = self.input_state.mouse_position;
+        self.state.hovered = hover;
+        let root = self.layout_stack[0].clone();
+        self.measure_layout(&root);
+        self.state.measures = self.measures;
+        self.state.grids = self.grids;
+
+        // merge the layers bottom to top
+        self.layer_buffers[self.layer as usize] = self.command_buffer;
+        let commands = self.layer_buffers.into_iter().flatten().collect();
+        UIResult {
+            new_state: self.state,
+            commands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    const MOCK_TEXT_HEIGHT: u32 = 16;
+    const MOCK_TEXT_WIDTH: u32 = 8;
+
+    use super::*;
+    use std::cell::Cell;
+    fn mock_font_info() -> impl FontInformation {
+        struct MockFontInfo;
+        impl FontInformation for MockFontInfo {
+            fn compute_text_size(&self, text: &str, scale: f32) -> Vec2 {
+                let scale = scale.max(0.0);
+                Vec2 {
+                    x: (text.len() as f32 * MOCK_TEXT_WIDTH as f32 * scale).ceil() as u32,
+                    y: (MOCK_TEXT_HEIGHT as f32 * scale).ceil() as u32,
+                }
+            }
+        }
+        MockFontInfo
+    }
+
+    /// Runs one frame with the mock font, drawing with `draw` before ending it
+    fn run_frame<T>(
+        state: UIState,
+        input: UIInputState,
+        draw: impl FnOnce(&mut UIContext) -> T,
+    ) -> (UIResult, T) {
+        let font_info = mock_font_info();
+        let mut ctx = UIContext::new(state, &font_info, input);
+        let ret = draw(&mut ctx);
+        (ctx.end(), ret)
+    }
+
+    #[test]
+    fn layout() {
+        const SECTION_TEXT_LEN: u32 = 9;
+
+        let input_state = UIInputState {
+            activate_button: ButtonState::Up,
+            focus_next_button: ButtonState::Up,
+            mouse_position: Vec2::zero(),
+            ..Default::default()
+        };
+
+        let font_info = mock_font_info();
+        let ui_state = UIState::new();
+        let mut ctx = super::UIContext::new(ui_state, &font_info, input_state);
+        // draw a horizontal group of texts, each with a vertical layout of text inside
+        ctx.layout(LayoutDirection::Horizontal, Some(4), false, |ctx| {
+            let main_layout = ctx.get_current_layout().clone();
+            for i in 0..3 {
+                let label = format!("Section {}", i);
+                assert!(
+                    label.len() as u32 == SECTION_TEXT_LEN,
+                    "broken test assertion"
+                );
+
+                ctx.text_layout(label);
+
+                for j in 0..2 {
+                    let sub_label = format!("Section {} item {}", i, j);
+
+                    ctx.layout(LayoutDirection::Vertical, Some(2), false, |ctx| {
+                        ctx.text_layout(sub_label);
+
+                        let sub_layout = ctx.get_current_layout();
+                        assert_eq!(
+                            sub_layout.top_left.y,
+                            main_layout.top_left.y + MOCK_TEXT_HEIGHT + sub_layout.spacing
+                        );
+                    });
+                }
+            }
+        });
+
+        println!("layout {:?}", ctx.get_current_layout());
+
+        assert_eq!(ctx.command_buffer.len(), 9);
+    }
+
+    #[test]
+    fn nested_layout_size_propagates() {
+        let font_info = mock_font_info();
+        let ui_state = UIState::new();
+        let mut ctx = super::UIContext::new(ui_state, &font_info, UIInputState::default());
+
+        ctx.layout(LayoutDirection::Horizontal, Some(4), false, |ctx| {
+            let parent_before = ctx.get_current_layout().clone();
+            let child_layout = ctx.layout(LayoutDirection::Vertical, Some(3), false, |ctx| {
+                ctx.text_layout("Hi".into());
+                ctx.text_layout("WiderText".into());
+                ctx.get_current_layout().clone()
+            });
+            assert_eq!(child_layout.size.x, MOCK_TEXT_WIDTH * 9);
+            assert_eq!(child_layout.size.y, MOCK_TEXT_HEIGHT * 2 + 3);
+
+            let parent_after = ctx.get_current_layout().clone();
+
+            assert_eq!(
+                parent_after.top_left.x,
+                parent_before.top_left.x + child_layout.size.x + parent_after.spacing
+            );
+            assert_eq!(parent_after.size.x, child_layout.size.x);
+            assert_eq!(parent_after.size.y, child_layout.size.y);
+        });
+    }
+
+    #[test]
+    fn layout_at_uses_given_position_for_background() {
+        let font_info = mock_font_info();
+        let mut ctx = super::UIContext::new(UIState::new(), &font_info, UIInputState::default());
+
+        let layout_pos = Vec2 { x: 20, y: 30 };
+        ctx.layout_at(layout_pos, LayoutDirection::Vertical, 2, true, |ctx| {
+            ctx.text_layout("abc".into())
+        });
+
+        assert_eq!(ctx.command_buffer.len(), 2);
+        match &ctx.command_buffer[0] {
+            DrawCommand::DrawRect { draw_data } => {
+                assert_eq!(draw_data.rect.top_left, layout_pos);
+                assert_eq!(
+                    draw_data.rect.size,
+                    Vec2 {
+                        x: MOCK_TEXT_WIDTH * 3,
+                        y: MOCK_TEXT_HEIGHT
+                    }
+                );
+            }
+            _ => panic!("expected layout background to be a rect draw"),
+        }
+    }
+
+    #[test]
+    fn layout_pads_aligns_and_justifies_children() {
+        // a padded column of two buttons and a row spreading two more across 100px
+        let frame = |state: UIState, align: Align| {
+            let (result, rects) = run_frame(state, UIInputState::default(), |ctx| {
+                ctx.set_next_layout_style(LayoutStyle {
+                    padding: Insets::all(10),
+                    align,
+                    ..Default::default()
+                });
+                ctx.layout(LayoutDirection::Vertical, Some(4), true, |ui| {
+                    let wide = ui.button_layout(Vec2::zero(), "AAAA".into()).rect;
+                    ui.set_next_margin(Insets::new(0, 6, 0, 0));
+                    let narrow = ui.button_layout(Vec2::zero(), "AA".into()).rect;
+                    ui.set_next_layout_style(LayoutStyle {
+                        justify: Justify::SpaceBetween,
+                        min_size: Vec2::new(100, 0),
+                        ..Default::default()
+                    });
+                    let (first, last) =
+                        ui.layout(LayoutDirection::Horizontal, Some(0), false, |ui| {
+                            let first = ui.button_layout(Vec2::zero(), "A".into()).rect;
+                            (first, ui.button_layout(Vec2::zero(), "B".into()).rect)
+                        });
+                    [wide, narrow, first, last]
+                })
+            });
+            let background = match &result.commands[0] {
+                DrawCommand::DrawRect { draw_data } => draw_data.rect,
+                _ => panic!("expected layout background to be a rect draw"),
+            };
+            (result.new_state, rects, background)
+        };
+        let top_lefts = |rects: [Rect; 4]| rects.map(|rect| rect.top_left);
+
+        // children settle once the column has measured its widest child
+        let (state, ..) = frame(UIState::new(), Align::Center);
+        let (state, rects, background) = frame(state, Align::Center);
+        assert_eq!(
+            top_lefts(rects),
+            [
+                Vec2::new(10 + (100 - 32) / 2, 10),
+                Vec2::new(10 + (100 - 16) / 2, 10 + 16 + 4 + 6),
+                Vec2::new(10, 56),
+                Vec2::new(10 + 100 - MOCK_TEXT_WIDTH, 56),
+            ]
+        );
+        assert_eq!(
+            background,
+            Rect {
+                top_left: Vec2::zero(),
+                size: Vec2::new(100 + 20, 56 + 16 + 10),
+            }
+        );
+
+        // stretched children fill the column
+        let (_, rects, _) = frame(state, Align::Stretch);
+        assert_eq!(rects[0].size, Vec2::new(100, MOCK_TEXT_HEIGHT));
+        assert_eq!(rects[1].top_left, Vec2::new(10, 36));
+    }
+
+    #[test]
+    fn stretched_children_shrink_with_their_content() {
+        // a stretching column with a button and a row of text, both as wide as the widest
+        let frame = |state: UIState, label: &str| {
+            let (result, (button, row, column)) =
+                run_frame(state, UIInputState::default(), |ctx| {
+                    ctx.set_next_layout_style(LayoutStyle {
+                        align: Align::Stretch,
+                        ..Default::default()
+                    });
+                    ctx.layout(LayoutDirection::Vertical, Some(0), false, |ui| {
+                        let button = ui.button_layout(Vec2::zero(), label.into()).rect;
+                        ui.set_next_id(WidgetId::ROOT.with("row"));
+                        ui.layout(LayoutDirection::Horizontal, None, false, |ui| {
+                            ui.text_layout("AA".into());
+                        });
+                        let row = ui.measures[&WidgetId::ROOT.with("row")].size;
+                        (button, row, ui.get_current_layout().size)
+                    })
+                });
+            (result.new_state, button.size.x, row.x, column.x)
+        };
+        let wide = MOCK_TEXT_WIDTH * 10;
+        let (state, ..) = frame(UIState::new(), "AAAAAAAAAA");
+        let (state, button, row, column) = frame(state, "AAAAAAAAAA");
+        assert_eq!((button, row, column), (wide, wide, wide));
+
+        // the column takes a frame to measure its children narrower, then stays narrow
+        let (mut state, ..) = frame(state, "A");
+        for _ in 0..3 {
+            let (next, button, row, column) = frame(state, "A");
+            let narrow = MOCK_TEXT_WIDTH * 2;
+            assert_eq!((button, row, column), (narrow, narrow, narrow));
+            state = next;
+        }
+    }
+
+    #[test]
+    fn fixed_layout_sizes_children_by_length() {
+        let mut slider = SliderState::new_range(0..10, 5, 1);
+        // a 300x100 row: a fixed slider, two filling what it leaves 1:3, all stretched down
+        let mut frame = |state: UIState| {
+            let (result, (widths, size)) = run_frame(state, UIInputState::default(), |ctx| {
+                ctx.set_next_layout_style(LayoutStyle {
+                    align: Align::Stretch,
+                    width: Some(300),
+                    height: Some(100),
+                    ..Default::default()
+                });
+                ctx.layout(LayoutDirection::Horizontal, Some(10), false, |ui| {
+                    let mut widths = vec![];
+                    for length in [Length::Fixed(80), Length::Fill(1.0), Length::Fill(3.0)] {
+                        ui.set_next_length(length);
+                        let rect = ui.slider_layout(Vec2::new(20, 20), &mut slider).rect;
+                        assert_eq!(rect.size.y, 100);
+                        widths.push(rect.size.x);
+                    }
+                    (widths, ui.get_current_layout().size)
+                })
+            });
+            (result.new_state, widths, size)
+        };
+
+        // fills settle once the row has measured the space the others take
+        let (state, _, size) = frame(UIState::new());
+        assert_eq!(size, Vec2::new(300, 100));
+        let (_, widths, size) = frame(state);
+        assert_eq!(widths, [80, 50, 150]);
+        assert_eq!(size, Vec2::new(300, 100));
+
+        // fractions are of the space inside the padding
+        let font_info = mock_font_info();
+        let mut ctx = UIContext::new(UIState::new(), &font_info, UIInputState::default());
+        ctx.set_next_layout_style(LayoutStyle {
+            padding: Insets::all(10),
+            width: Some(220),
+            ..Default::default()
+        });
+        let rect = ctx.layout(LayoutDirection::Horizontal, None, false, |ui| {
+            ui.set_next_length(Length::Fraction(0.5));
+            ui.slider_layout(Vec2::new(20, 20), &mut slider).rect
+        });
+        assert_eq!(rect.size.x, 100);
+
+        // in a row without a size, filling and fractional layouts keep their own size
+        let mut state = UIState::new();
+        for _ in 0..4 {
+            let (result, sizes) = run_frame(state, UIInputState::default(), |ctx| {
+                ctx.layout(LayoutDirection::Horizontal, None, false, |ui| {
+                    [Length::Fill(1.0), Length::Fraction(0.5)].map(|length| {
+                        ui.set_next_length(length);
+                        ui.layout(LayoutDirection::Vertical, None, false, |ui| {
+                            ui.text_layout("hello".into());
+                            ui.get_current_layout().size
+                        })
+                    })
+                })
+            });
+            let hello = Vec2::new(MOCK_TEXT_WIDTH * 5, MOCK_TEXT_HEIGHT);
+            assert_eq!(sizes, [hello, hello]);
+            state = result.new_state;
+        }
+    }
+
+    #[test]
+    fn grid_lines_up_columns_across_rows() {
+        let mut slider = SliderState::new_range(0..10, 5, 1);
+        let columns = [
+            GridColumn::Auto,
+            GridColumn::Fixed(40),
+            GridColumn::Fraction(0.25),
+        ];
+        // (span, length, size) of each cell
+        let cells = [
+            (1, None, Vec2::new(30, 20)),
+            (1, None, Vec2::new(20, 20)),
+            (1, Some(Length::Fill(1.0)), Vec2::new(20, 20)),
+            (1, None, Vec2::new(60, 30)),
+            (2, Some(Length::Fill(1.0)), Vec2::new(20, 20)),
+            (1, None, Vec2::new(10, 20)),
+            (3, Some(Length::Fill(1.0)), Vec2::new(20, 20)),
+        ];
+        let mut frame = |state: UIState| {
+            let (result, (rects, size)) = run_frame(state, UIInputState::default(), |ctx| {
+                ctx.set_next_layout_style(LayoutStyle {
+                    padding: Insets::all(5),
+                    align: Align::Center,
+                    width: Some(230),
+                    ..Default::default()
+                });
+                ctx.grid(&columns, Vec2::new(10, 4), false, |ui| {
+                    let mut rects = vec![];
+                    for (span, length, size) in cells {
+                        ui.set_next_span(span);
+                        if let Some(length) = length {
+                            ui.set_next_length(length);
+                        }
+                        rects.push(ui.slider_layout(size, &mut slider).rect);
+                    }
+                    (rects, ui.get_current_layout().size)
+                })
+            });
+            (result.new_state, rects, size)
+        };
+
+        // the auto column settles once its widest cell has been measured
+        let (state, rects, _) = frame(UIState::new());
+        assert_eq!(rects[1].top_left.x, 5 + 30 + 10);
+        let (_, rects, size) = frame(state);
+        let tops: Vec<Vec2> = rects.iter().map(|rect| rect.top_left).collect();
+        assert_eq!(
+            tops,
+            [
+                Vec2::new(5, 5),
+                Vec2::new(75, 5),
+                Vec2::new(125, 5),
+                Vec2::new(5, 29),
+                // centered in the taller row
+                Vec2::new(75, 34),
+                Vec2::new(5, 63),
+                // doesn't fit after the last cell, so takes the next row
+                Vec2::new(5, 87),
+            ]
+        );
+        let widths: Vec<u32> = rects.iter().map(|rect| rect.size.x).collect();
+        assert_eq!(widths, [30, 20, 50, 60, 100, 10, 170]);
+        assert_eq!(size, Vec2::new(230, 112));
+    }
+
+    #[test]
+    fn grid_columns_keep_their_policies_as_cells_change() {
+        let mut slider = SliderState::new_range(0..10, 5, 1);
+        let columns = [GridColumn::Auto, GridColumn::Fixed(40), GridColumn::Auto];
+        // a row with a cell too wide for the fixed column, then a cell filling the first column
+        let mut frame = |state: UIState, width: u32| {
+            let (result, (rects, size)) = run_frame(state, UIInputState::default(), |ctx| {
+                ctx.grid(&columns, Vec2::new(10, 0), false, |ui| {
+                    let mut rects = vec![];
+                    for size in [width, 100, 10] {
+                        rects.push(ui.slider_layout(Vec2::new(size, 20), &mut slider).rect);
+                    }
+                    ui.set_next_length(Length::Fill(1.0));
+                    rects.push(ui.slider_layout(Vec2::new(20, 20), &mut slider).rect);
+                    (rects, ui.get_current_layout().size)
+                })
+            });
+            (result.new_state, rects, size)
+        };
+
+        let (state, ..) = frame(UIState::new(), 100);
+        let (mut state, rects, size) = frame(state, 100);
+        // the fixed column stays 40 wide, its cell overflowing into the next
+        assert_eq!(rects[1].top_left.x, 110);
+        assert_eq!(rects[2].top_left.x, 110 + 40 + 10);
+        assert_eq!(rects[3].size.x, 100);
+        assert_eq!(size.x, 100 + 10 + 40 + 10 + 10);
+
+        // the auto column shrinks once its widest cell does, the filling cell with it
+        let (next, ..) = frame(state, 30);
+        state = next;
+        for _ in 0..3 {
+            let (next, rects, size) = frame(state, 30);
+            assert_eq!(rects[2].top_left.x, 30 + 10 + 40 + 10);
+            assert_eq!(rects[3].size.x, 30);
+            assert_eq!(size.x, 30 + 10 + 40 + 10 + 10);
+            state = next;
+        }
+    }
+
+    #[test]
+    fn flow_wraps_children_into_rows() {
+        let font_info = mock_font_info();
+        let mut slider = SliderState::new_range(0..10, 5, 1);
+        let mut ctx = UIContext::new(UIState::new(), &font_info, UIInputState::default());
+        ctx.set_next_layout_style(LayoutStyle {
+            padding: Insets::all(5),
+            ..Default::default()
+        });
+        let (rects, layout) = ctx.flow(100, Vec2::new(10, 4), true, |ui| {
+            let mut rects = vec![];
+            for (length, size) in [
+                (None, Vec2::new(40, 20)),
+                (None, Vec2::new(40, 30)),
+                (None, Vec2::new(30, 20)),
+                (Some(Length::Fill(1.0)), Vec2::new(20, 20)),
+                (None, Vec2::new(120, 10)),
+            ] {
+                if let Some(length) = length {
+                    ui.set_next_length(length);
+                }
+                rects.push(ui.slider_layout(size, &mut slider).rect);
+            }
+            (rects, ui.get_current_layout().clone())
+        });
+        let placed: Vec<(Vec2, u32)> = rects.iter().map(|r| (r.top_left, r.size.x)).collect();
+        assert_eq!(
+            placed,
+            [
+                (Vec2::new(5, 5), 40),
+                (Vec2::new(55, 5), 40),
+                // the row is as tall as its tallest child
+                (Vec2::new(5, 39), 30),
+                // filling the rest of the row
+                (Vec2::new(45, 39), 50),
+                // too wide for any row, so alone on its own
+                (Vec2::new(5, 63), 120),
+            ]
+        );
+        assert_eq!(layout.size, Vec2::new(130, 78));
+
+        // the background covers every row
+        let result = ctx.end();
+        let background = result.commands.iter().find_map(|cmd| match cmd {
+            DrawCommand::DrawRect { draw_data }
+                if draw_data.role == UIDrawRole::LayoutBackground =>
+            {
+                Some(draw_data.rect)
+            }
+            _ => None,
+        });
+        assert_eq!(background.map(|rect| rect.size), Some(layout.size));
+    }
+
+    #[test]
+    fn left_labels_share_the_widest_width_measured() {
+        let font_info = mock_font_info();
+        let frame = |state: UIState| {
+            let (result, (short, long)) = run_frame(state, UIInputState::default(), |ctx| {
+                ctx.layout(LayoutDirection::Vertical, Some(4), false, |ui| {
+                    let (mut a, mut b) = (false, false);
+                    let size = Vec2::new(20, 20);
+                    (
+                        ui.checkbox_layout_label_left(size, &mut a, "A".into(), 1.0),
+                        ui.checkbox_layout_label_left(size, &mut b, "Longer".into(), 1.0),
+                    )
+                })
+            });
+            (result.new_state, short, long)
+        };
+
+        // the first frame only knows the labels drawn so far
+        let (state, short, long) = frame(UIState::new());
+        assert_eq!(short.rect.top_left.x, MOCK_TEXT_WIDTH + 4);
+        assert_eq!(long.rect.top_left.x, MOCK_TEXT_WIDTH * 6 + 4);
+        let (state, short, long) = frame(state);
+        assert_eq!(short.rect.top_left.x, long.rect.top_left.x);
+
+        // widget sizes are kept for the next frame too
+        let ctx = UIContext::new(state, &font_info, UIInputState::default());
+        assert_eq!(ctx.measured_size(short.id), Some(Vec2::new(20, 20)));
+        assert_eq!(ctx.measured_size(WidgetId::ROOT.with("missing")), None);
+    }
+
+    #[test]
+    fn button_click() {
+        let font_info = mock_font_info();
+        let ui_state = UIState::new();
+
+        // first frame: mouse down over button
+        let mut ctx = super::UIContext::new(
+            ui_state,
+            &font_info,
+            UIInputState {
+                activate_button: ButtonState::Down,
+                ..Default::default()
+            },
+        );
+        let clicked = ctx
+            .button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into())
+            .clicked;
+        assert!(!clicked, "button should not register click on mouse down");
+        let result = ctx.end();
+
+        // second frame: mouse up over button
+        let mut ctx = super::UIContext::new(
+            result.new_state,
+            &font_info,
+            UIInputState {
+                activate_button: ButtonState::Released,
+                ..Default::default()
+            },
+        );
+        let clicked = ctx
+            .button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into())
+            .clicked;
+        assert!(clicked, "button should register click on mouse up");
+    }
+
+    #[test]
+    fn button_click_outside() {
+        let font_info = mock_font_info();
+        let ui_state = UIState::new();
+
+        let input_state = UIInputState {
+            mouse_position: Vec2 { x: 100, y: 100 },
+            activate_button: ButtonState::Down,
+            ..Default::default()
+        };
+
+        // first frame: mouse down outside button
+        let mut ctx = super::UIContext::new(ui_state, &font_info, input_state);
+        let clicked = ctx
+            .button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into())
+            .clicked;
+        assert!(
+            !clicked,
+            "button should not register click on mouse down outside"
+        );
+        let result = ctx.end();
+
+        let input_state = UIInputState {
+            mouse_position: Vec2 { x: 100, y: 100 },
+            ..Default::default()
+        };
+
+        // second frame: mouse up outside button
+        let mut ctx = super::UIContext::new(result.new_state, &font_info, input_state);
+        let clicked = ctx
+            .button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into())
+            .clicked;
+        assert!(
+            !clicked,
+            "button should not register click on mouse up outside"
+        );
+    }
+
+    #[test]
+    fn enter_key_activates_focused_button() {
+        let font_info = mock_font_info();
+        let button_padding = Vec2::zero();
+        let button_pos = Vec2::zero();
+        let mouse_far = Vec2 { x: 999, y: 999 };
+
+        // focus the button
+        let mut ctx = super::UIContext::new(
+            UIState::new(),
+            &font_info,
+            UIInputState {
+                focus_next_button: ButtonState::Released,
+                mouse_position: mouse_far,
+                ..Default::default()
+            },
+        );
+        ctx.button(button_pos, button_padding, "A".into());
+        let result = ctx.end();
+
+        // key down should mark it active but not click yet
+        let mut ctx = super::UIContext::new(
+            result.new_state,
+            &font_info,
+            UIInputState {
+                activate_button: ButtonState::Down,
+                mouse_position: mouse_far,
+                ..Default::default()
+            },
+        );
+        let clicked = ctx.button(button_pos, button_padding, "A".into()).clicked;
+        assert!(
+            !clicked,
+            "activate key down alone should not register a click"
+        );
+        let state = ctx.end().new_state;
+
+        // releasing the key should click the focused button even without hover
+        let mut ctx = super::UIContext::new(
+            state,
+            &font_info,
+            UIInputState {
+                activate_button: ButtonState::Released,
+                mouse_position: mouse_far,
+                ..Default::default()
+            },
+        );
+        let clicked = ctx.button(button_pos, button_padding, "A".into()).clicked;
+        assert!(clicked, "activate key up should click the focused button");
+    }
+
+    #[test]
+    fn tab_focus_advances_through_focusables() {
+        let font_info = mock_font_info();
+        let button_padding = Vec2::zero();
+        let first_button_pos = Vec2::zero();
+        let second_button_pos = Vec2 { x: 50, y: 0 };
+
+        // first tab press should focus the first registered control
+        let mut ctx = super::UIContext::new(
+            UIState::new(),
+            &font_info,
+            UIInputState {
+                focus_next_button: ButtonState::Released,
+                ..Default::default()
+            },
+        );
+        ctx.button(first_button_pos, button_padding, "A".into());
+        ctx.button(second_button_pos, button_padding, "B".into());
+        let result = ctx.end();
+        assert_eq!(
+            result.new_state.focused_rect().unwrap().top_left,
+            first_button_pos
+        );
+
+        // next press should advance to the next focusable
+        let mut ctx = super::UIContext::new(
+            result.new_state,
+            &font_info,
+            UIInputState {
+                focus_next_button: ButtonState::Released,
+                ..Default::default()
+            },
+        );
+        ctx.button(first_button_pos, button_padding, "A".into());
+        ctx.button(second_button_pos, button_padding, "B".into());
+        let result = ctx.end();
+        assert_eq!(
+            result.new_state.focused_rect().unwrap().top_left,
+            second_button_pos
+        );
+
+        // pressing again should wrap back to the first
+        let mut ctx = super::UIContext::new(
+            result.new_state,
+            &font_info,
+            UIInputState {
+                focus_next_button: ButtonState::Released,
+                ..Default::default()
+            },
+        );
+        ctx.button(first_button_pos, button_padding, "A".into());
+        ctx.button(second_button_pos, button_padding, "B".into());
+        let result = ctx.end();
+        assert_eq!(
+            result.new_state.focused_rect().unwrap().top_left,
+            first_button_pos
+        );
+    }
+
+    #[test]
+    fn focus_moves_backwards_stops_at_ends_and_cancels() {
+        let positions = [Vec2::zero(), Vec2::new(50, 0), Vec2::new(100, 0)];
+        let frame = |state: UIState, input: UIInputState, wrap: bool| {
+            let (result, _) = run_frame(state, input, |ctx| {
+                ctx.set_focus_wrap(wrap);
+                for (i, pos) in positions.iter().enumerate() {
+                    ctx.button(*pos, Vec2::zero(), i.to_string());
+                }
+            });
+            result.new_state
+        };
+        let prev = || UIInputState {
+            focus_prev_button: ButtonState::Released,
+            ..Default::default()
+        };
+        let focused_pos = |state: &UIState| state.focused_rect().map(|rect| rect.top_left);
+
+        // backwards from nothing focuses the last, then walks back and wraps
+        let state = frame(UIState::new(), prev(), true);
+        assert_eq!(focused_pos(&state), Some(positions[2]));
+        let state = frame(state, prev(), true);
+        assert_eq!(focused_pos(&state), Some(positions[1]));
+        let state = frame(state, prev(), true);
+        let state = frame(state, prev(), true);
+        assert_eq!(focused_pos(&state), Some(positions[2]));
+
+        // without wrapping, focus stays at the ends
+        let state = frame(
+            state,
+            UIInputState {
+                focus_next_button: ButtonState::Released,
+                ..Default::default()
+            },
+            false,
+        );
+        assert_eq!(focused_pos(&state), Some(positions[2]));
+        let state = frame(state, prev(), false);
+        let state = frame(state, prev(), false);
+        let state = frame(state, prev(), false);
+        assert_eq!(focused_pos(&state), Some(positions[0]));
+
+        // cancel clears focus
+        let state = frame(
+            state,
+            UIInputState {
+                cancel_button: ButtonState::Down,
+                ..Default::default()
+            },
+            true,
+        );
+        assert_eq!(state.focused_id(), None);
+    }
+
+    #[test]
+    fn directional_focus_moves_spatially_and_yields_to_widgets() {
+        // a 2x2 grid of buttons with a slider underneath
+        let positions = [
+            Vec2::zero(),
+            Vec2::new(50, 0),
+            Vec2::new(0, 50),
+            Vec2::new(50, 50),
+        ];
+        let slider_pos = Vec2::new(0, 100);
+        let mut slider_state = SliderState::new(0_u32, 10_u32, 5_u32, 1_u32);
+        let slider_changed = Cell::new(false);
+        let mut frame = |state: UIState, input: UIInputState, wrap: bool| {
+            let (result, changed) = run_frame(state, input, |ctx| {
+                ctx.set_directional_focus(true);
+                ctx.set_focus_wrap(wrap);
+                for (i, pos) in positions.iter().enumerate() {
+                    ctx.button(*pos, Vec2::zero(), i.to_string());
+                }
+                let slider_rect = Rect {
+                    top_left: slider_pos,
+                    size: Vec2::new(100, 10),
+                };
+                ctx.slider(slider_rect, &mut slider_state).changed
+            });
+            slider_changed.set(changed);
+            result.new_state
+        };
+        let press = |key: fn(&mut UIInputState) -> &mut ButtonState| {
+            let mut input = UIInputState::default();
+            *key(&mut input) = ButtonState::Down;
+            input
+        };
+        let right = || press(|input| &mut input.move_right);
+        let down = || press(|input| &mut input.move_down);
+        let focused_pos = |state: &UIState| state.focused_rect().map(|rect| rect.top_left);
+
+        // with nothing focused, a move focuses the first widget
+        let state = frame(UIState::new(), right(), true);
+        assert_eq!(focused_pos(&state), Some(positions[0]));
+        let state = frame(state, right(), true);
+        assert_eq!(focused_pos(&state), Some(positions[1]));
+        let state = frame(state, down(), true);
+        assert_eq!(focused_pos(&state), Some(positions[3]));
+
+        // moving off the end of a row wraps to its start, unless wrapping is off
+        let state = frame(state, right(), false);
+        assert_eq!(focused_pos(&state), Some(positions[3]));
+        let state = frame(state, right(), true);
+        assert_eq!(focused_pos(&state), Some(positions[2]));
+
+        // the slider uses left/right itself but lets up/down through
+        let state = frame(state, down(), true);
+        assert_eq!(focused_pos(&state), Some(slider_pos));
+        assert!(!slider_changed.get());
+        let state = frame(state, right(), true);
+        assert_eq!(focused_pos(&state), Some(slider_pos));
+        // the step is reported on the frame of the key press
+        assert!(slider_changed.get());
+        // the button above nearest the slider's centre wins
+        let state = frame(state, press(|input| &mut input.move_up), true);
+        assert_eq!(focused_pos(&state), Some(positions[3]));
+        assert_eq!(slider_state.value, 6);
+    }
+
+    #[test]
+    fn focus_can_be_requested_queried_and_cleared() {
+        let font_info = mock_font_info();
+        // draws two buttons, requesting focus on the second if asked, and returns both IDs
+        let frame = |state: UIState, input: UIInputState, request: bool| {
+            let (result, (ids, transitions)) = run_frame(state, input, |ctx| {
+                ctx.button(Vec2::zero(), Vec2::zero(), "A".into());
+                let first = ctx.last_widget_id().unwrap();
+                ctx.button(Vec2::new(50, 0), Vec2::zero(), "B".into());
+                let second = ctx.last_widget_id().unwrap();
+                if request {
+                    ctx.request_focus(second);
+                }
+                let transitions = [first, second]
+                    .map(|id| (ctx.is_focused(id), ctx.focus_gained(id), ctx.focus_lost(id)));
+                ([first, second], transitions)
+            });
+            (result.new_state, ids, transitions)
+        };
+
+        // requests win over keyboard traversal and show from the next frame
+        let tab = || UIInputState {
+            focus_next_button: ButtonState::Released,
+            ..Default::default()
+        };
+        let (state, [_, second], transitions) = frame(UIState::new(), tab(), true);
+        assert_eq!(state.focused_id(), Some(second));
+        assert_eq!(transitions, [(false, false, false); 2]);
+
+        let (state, _, transitions) = frame(state, UIInputState::default(), false);
+        assert_eq!(transitions, [(false, false, false), (true, true, false)]);
+        let (state, _, transitions) = frame(state, tab(), false);
+        assert_eq!(transitions, [(false, false, false), (true, false, false)]);
+        let (state, _, transitions) = frame(state, UIInputState::default(), false);
+        assert_eq!(transitions, [(true, true, false), (false, false, true)]);
+
+        // blurring clears focus at the end of the frame
+        let mut ctx = UIContext::new(state, &font_info, UIInputState::default());
+        ctx.blur();
+        assert!(ctx.end().new_state.focused_id().is_none());
+
+        // focus can also be set between frames
+        let mut state = UIState::new();
+        state.set_focused(Some(second));
+        let (_, _, transitions) = frame(state, UIInputState::default(), false);
+        assert_eq!(transitions[1], (true, true, false));
+    }
+
+    #[test]
+    fn trapping_focus_scope_confines_traversal_and_restores_focus() {
+        let frame = |state: UIState, input: UIInputState, modal: bool| {
+            let (result, _) = run_frame(state, input, |ctx| {
+                ctx.button(Vec2::zero(), Vec2::zero(), "A".into());
+                ctx.button(Vec2::new(50, 0), Vec2::zero(), "B".into());
+                if modal {
+                    ctx.with_focus_scope(true, |ctx| {
+                        ctx.button(Vec2::new(0, 50), Vec2::zero(), "C".into());
+                        ctx.button(Vec2::new(50, 50), Vec2::zero(), "D".into());
+                    });
+                }
+            });
+            result.new_state
+        };
+        let tab = || UIInputState {
+            focus_next_button: ButtonState::Released,
+            ..Default::default()
+        };
+        let focused_pos = |state: &UIState| state.focused_rect().map(|rect| rect.top_left);
+
+        let state = frame(UIState::new(), tab(), false);
+        assert_eq!(focused_pos(&state), Some(Vec2::zero()));
+
+        // opening the modal takes focus, and tab cycles within it
+        let state = frame(state, UIInputState::default(), true);
+        assert_eq!(focused_pos(&state), Some(Vec2::new(0, 50)));
+        let state = frame(state, tab(), true);
+        assert_eq!(focused_pos(&state), Some(Vec2::new(50, 50)));
+        let state = frame(state, tab(), true);
+        assert_eq!(focused_pos(&state), Some(Vec2::new(0, 50)));
+
+        // closing it gives focus back
+        let state = frame(state, UIInputState::default(), false);
+        assert_eq!(focused_pos(&state), Some(Vec2::zero()));
+        let state = frame(state, tab(), false);
+        assert_eq!(focused_pos(&state), Some(Vec2::new(50, 0)));
+    }
+
+    #[test]
+    fn response_reports_presses_drags_and_double_clicks() {
+        let frame = |state: UIState, input: UIInputState| {
+            let (result, response) = run_frame(state, input, |ctx| {
+                ctx.button(Vec2::zero(), Vec2::new(8, 4), "A".into())
+            });
+            (result.new_state, response)
+        };
+        let input = |activate_button: ButtonState, time: f64| UIInputState {
+            mouse_position: Vec2::new(2, 2),
+            activate_button,
+            time,
+            ..Default::default()
+        };
+
+        let (state, response) = frame(UIState::new(), input(ButtonState::Up, 0.0));
+        assert!(!response.hovered);
+        assert_eq!(response.rect.size, Vec2::new(16, 20));
+
+        // press, hold and release
+        let (state, response) = frame(state, input(ButtonState::Down, 0.0));
+        assert!(response.hovered && response.pressed && !response.dragging);
+        let (state, response) = frame(state, input(ButtonState::Held, 0.1));
+        assert!(response.drag_started && response.dragging);
+        let (state, response) = frame(state, input(ButtonState::Held, 0.1));
+        assert!(!response.drag_started && response.dragging);
+        let (state, response) = frame(state, input(ButtonState::Released, 0.2));
+        assert!(response.clicked && response.drag_stopped && !response.double_clicked);
+
+        // a quick second click is a double click, a third starts over
+        let (state, _) = frame(state, input(ButtonState::Down, 0.3));
+        let (state, response) = frame(state, input(ButtonState::Released, 0.3));
+        assert!(response.clicked && response.double_clicked);
+        let (state, _) = frame(state, input(ButtonState::Down, 0.4));
+        let (state, response) = frame(state, input(ButtonState::Released, 0.4));
+        assert!(response.clicked && !response.double_clicked);
+
+        // too slow for a double click
+        let (state, _) = frame(state, input(ButtonState::Down, 2.0));
+        let (state, response) = frame(state, input(ButtonState::Released, 2.0));
+        assert!(response.clicked && !response.double_clicked);
+
+        let (_, response) = frame(
+            state,
+            UIInputState {
+                secondary_button: ButtonState::Released,
+                ..input(ButtonState::Up, 3.0)
+            },
+        );
+        assert!(response.secondary_clicked && !response.clicked);
+    }
+
+    #[test]
+    fn context_menu_opens_on_secondary_click_and_closes_on_choice() {
+        let mut chosen = vec![];
+        let mut frame = |state: UIState, input: UIInputState| {
+            let (result, open) = run_frame(state, input, |ctx| {
+                let response = ctx.button(Vec2::zero(), Vec2::new(4, 4), "File".into());
+                let layout_before = ctx.get_current_layout().clone();
+                let open = ctx
+                    .context_menu(&response, 80, |ctx| {
+                        for label in ["Copy", "Paste"] {
+                            if ctx.menu_item(label.into()).clicked {
+                                chosen.push(label);
+                            }
+                        }
+                    })
+                    .is_some();
+                // the menu doesn't move the layout it was drawn from
+                let layout = ctx.get_current_layout();
+                assert_eq!(
+                    (layout.top_left, layout.size),
+                    (layout_before.top_left, layout_before.size)
+                );
+                open
+            });
+            (result.new_state, open)
+        };
+        let input = |mouse_position: Vec2| UIInputState {
+            mouse_position,
+            ..Default::default()
+        };
+        let right_click = |mouse_position: Vec2| UIInputState {
+            secondary_button: ButtonState::Released,
+            ..input(mouse_position)
+        };
+        let click = |mouse_position: Vec2, activate_button: ButtonState| UIInputState {
+            activate_button,
+            ..input(mouse_position)
+        };
+        // items are 24 high, the menu opening at the mouse
+        let on_button = Vec2::new(4, 4);
+        let paste = Vec2::new(10, 4 + 24 + 12);
+
+        let (state, open) = frame(UIState::new(), input(on_button));
+        assert!(!open);
+        let (state, open) = frame(state, right_click(on_button));
+        assert!(open);
+
+        // choosing an item closes the menu
+        let (state, _) = frame(state, input(paste));
+        let (state, _) = frame(state, click(paste, ButtonState::Down));
+        let (state, _) = frame(state, click(paste, ButtonState::Released));
+        let (state, open) = frame(state, input(paste));
+        assert!(!open);
+
+        // pressing outside or cancelling closes it too
+        let (state, _) = frame(state, right_click(on_button));
+        let (state, _) = frame(state, click(Vec2::new(300, 300), ButtonState::Down));
+        let (state, open) = frame(state, input(on_button));
+        assert!(!open);
+        let (state, _) = frame(state, right_click(on_button));
+        let (state, _) = frame(
+            state,
+            UIInputState {
+                cancel_button: ButtonState::Down,
+                ..input(on_button)
+            },
+        );
+        let (_, open) = frame(state, input(on_button));
+        assert!(!open);
+        assert_eq!(chosen, ["Paste"]);
+    }
+
+    #[test]
+    fn focus_survives_widget_moving() {
+        let font_info = mock_font_info();
+
+        let mut ctx = super::UIContext::new(
+            UIState::new(),
+            &font_info,
+            UIInputState {
+                focus_next_button: ButtonState::Released,
+                ..Default::default()
+            },
+        );
+        ctx.button(Vec2::zero(), Vec2::zero(), "A".into());
+        let state = ctx.end().new_state;
+        let focused = state.focused_id().expect("button should be focused");
+
+        // the same button shifted over should keep its focus
+        let moved_pos = Vec2::new(3, 7);
+        let mut ctx = super::UIContext::new(state, &font_info, UIInputState::default());
+        ctx.button(moved_pos, Vec2::zero(), "A".into());
+        let state = ctx.end().new_state;
+        assert_eq!(state.focused_id(), Some(focused));
+        assert_eq!(state.focused_rect().unwrap().top_left, moved_pos);
+    }
+
+    #[test]
+    fn slider_drag_survives_widget_moving() {
+        let font_info = mock_font_info();
+        let mut slider_state = SliderState::new(0_u32, 10_u32, 5_u32, 1_u32);
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(100, 12),
+        };
+
+        let mut ctx = UIContext::new(
+            UIState::new(),
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(10, 6),
+                activate_button: ButtonState::Down,
+                ..Default::default()
+            },
+        );
+        ctx.slider(rect, &mut slider_state);
+        let state = ctx.end().new_state;
+
+        // the slider moves down out from under the mouse, but the drag continues
+        let moved = Rect {
+            top_left: Vec2::new(0, 40),
+            ..rect
+        };
+        let mut ctx = UIContext::new(
+            state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(30, 6),
+                activate_button: ButtonState::Held,
+                ..Default::default()
+            },
+        );
+        assert!(ctx.slider(moved, &mut slider_state).changed);
+        assert_eq!(slider_state.value, 7);
+    }
+
+    #[test]
+    fn id_stack_scopes_widget_ids() {
+        let font_info = mock_font_info();
+        let mut ctx = super::UIContext::new(UIState::new(), &font_info, UIInputState::default());
+
+        let first = ctx.with_id("a", |ctx| ctx.make_id());
+        let second = ctx.with_id("b", |ctx| ctx.make_id());
+        assert_ne!(first, second);
+
+        // the same scope always produces the same ids
+        let again = ctx.with_id("a", |ctx| ctx.make_id());
+        assert_eq!(first, again);
+
+        // label ids are relative to the current scope
+        let label_id = ctx.id_from_label("Save");
+        assert_eq!(label_id, WidgetId::ROOT.with("Save"));
+        let scoped_label_id = ctx.with_id("a", |ctx| ctx.id_from_label("Save"));
+        assert_ne!(label_id, scoped_label_id);
+
+        // an explicit id is used as-is
+        let explicit = WidgetId::new("explicit");
+        ctx.set_next_id(explicit);
+        assert_eq!(ctx.make_id(), explicit);
+    }
+
+    #[test]
+    fn text_input_state_edits_on_char_boundaries() {
+        let mut state = TextInputState::new("héllo");
+        assert_eq!(state.caret, state.buffer.len());
+
+        state.move_left(false);
+        state.move_left(true);
+        state.move_left(true);
+        assert_eq!(state.selected_text(), "ll");
+
+        state.insert_str("y");
+        assert_eq!(state.buffer, "héyo");
+        assert_eq!(state.selection(), None);
+
+        // deleting backwards over a multi-byte char removes the whole char
+        state.move_left(false);
+        assert!(state.delete_backward());
+        assert_eq!(state.buffer, "hyo");
+        assert_eq!(state.caret, 1);
+
+        state.set_caret(0, false);
+        assert!(!state.delete_backward());
+        assert!(state.delete_forward());
+        assert_eq!(state.buffer, "yo");
+
+        state.select_all();
+        assert!(state.delete_forward());
+        assert!(state.buffer.is_empty());
+    }
+
+    #[test]
+    fn key_events_drive_named_buttons_and_shift_selects() {
+        let press = |key: Key, modifiers: Modifiers| KeyEvent {
+            key,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        };
+        let mut input = UIInputState {
+            key_events: vec![
+                press(Key::Tab, Modifiers::SHIFT),
+                press(Key::Left, Modifiers::NONE),
+                KeyEvent {
+                    pressed: false,
+                    ..press(Key::Escape, Modifiers::NONE)
+                },
+                press(Key::A, Modifiers::CTRL),
+            ],
+            ..Default::default()
+        };
+        input.apply_key_events(&UIState::new());
+        assert_eq!(input.focus_prev_button, ButtonState::Down);
+        assert_eq!(input.focus_next_button, ButtonState::Up);
+        assert_eq!(input.move_left, ButtonState::Down);
+        assert_eq!(input.cancel_button, ButtonState::Released);
+        // the queue is left for widgets that read it
+        assert_eq!(input.key_events.len(), 4);
+
+        // shift+left from the end of a focused field selects the last char
+        let font_info = mock_font_info();
+        let mut state = TextInputState::new("abc");
+        let mut ctx = UIContext::new(UIState::new(), &font_info, UIInputState::default());
+        let mut ui_state = UIState::new();
+        ui_state.focused = Some(ctx.make_id());
+        let mut input = UIInputState {
+            modifiers: Modifiers::SHIFT,
+            key_events: vec![press(Key::Left, Modifiers::SHIFT)],
+            ..Default::default()
+        };
+        input.apply_key_events(&UIState::new());
+        let mut ctx = UIContext::new(ui_state, &font_info, input);
+        ctx.text_input(
+            Rect {
+                top_left: Vec2::zero(),
+                size: Vec2::new(200, 24),
+            },
+            &mut state,
+        );
+        assert_eq!(state.selected_text(), "c");
+    }
+
+    #[test]
+    fn enter_and_space_activate_the_focused_widget() {
+        let font_info = mock_font_info();
+        let key = |key: Key, pressed: bool| KeyEvent {
+            key,
+            pressed,
+            repeat: false,
+            modifiers: Modifiers::NONE,
+        };
+        // focus on the first of two buttons, the mouse over the second
+        let frame = |state: UIState, events: Vec<KeyEvent>| {
+            let mut input = UIInputState {
+                mouse_position: Vec2::new(60, 4),
+                key_events: events,
+                ..Default::default()
+            };
+            input.apply_key_events(&state);
+            let (result, (first, second)) = run_frame(state, input, |ctx| {
+                let first = ctx.button(Vec2::zero(), Vec2::new(4, 4), "A".into());
+                (
+                    first,
+                    ctx.button(Vec2::new(50, 0), Vec2::new(4, 4), "B".into()),
+                )
+            });
+            (result.new_state, first, second)
+        };
+        let (mut state, first, _) = frame(UIState::new(), vec![]);
+        state.focused = Some(first.id);
+
+        // held over frames without events, the click lands on release
+        let (state, first, _) = frame(state, vec![key(Key::Enter, true)]);
+        assert!(!first.clicked);
+        let (state, first, _) = frame(state, vec![]);
+        assert!(first.dragging);
+        let (state, first, second) = frame(state, vec![key(Key::Enter, false)]);
+        assert!(first.clicked && !second.clicked);
+        let (state, ..) = frame(state, vec![key(Key::Space, true)]);
+        let (_, first, _) = frame(state, vec![key(Key::Space, false)]);
+        assert!(first.clicked);
+
+        // space typed into a focused field, with the mouse elsewhere, keeps it focused
+        let mut field = TextInputState::new("ab");
+        let mut ui_state = UIState::new();
+        let mut ctx = UIContext::new(UIState::new(), &font_info, UIInputState::default());
+        ui_state.focused = Some(ctx.make_id());
+        let mut input = UIInputState {
+            mouse_position: Vec2::new(500, 500),
+            key_events: vec![key(Key::Space, true)],
+            typed_chars: vec![' '],
+            ..Default::default()
+        };
+        input.apply_key_events(&ui_state);
+        let mut ctx = UIContext::new(ui_state, &font_info, input);
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(200, 24),
+        };
+        let response = ctx.text_input(rect, &mut field);
+        assert_eq!(field.buffer, "ab ");
+        assert!(ctx.end().new_state.focused_id() == Some(response.id));
+    }
+
+    #[test]
+    fn shortcuts_trigger_by_focus_scope_and_label_widgets() {
+        let font_info = mock_font_info();
+        let save = Shortcut::new(Modifiers::CTRL, Key::S);
+        let undo = Shortcut::new(Modifiers::CTRL.with(Modifiers::SHIFT), Key::Z);
+        assert_eq!(undo.to_string(), "Ctrl+Shift+Z");
+
+        // a global save and undo, a button undoing in its own scope, and a modal with its own save
+        let frame = |state: UIState, pressed: Shortcut, modal: bool| {
+            let input = UIInputState {
+                key_events: vec![KeyEvent {
+                    key: pressed.key,
+                    pressed: true,
+                    repeat: false,
+                    modifiers: pressed.modifiers,
+                }],
+                ..Default::default()
+            };
+            let (result, (triggered, button)) = run_frame(state, input, |ctx| {
+                let mut triggered = vec![ctx.shortcut(save), ctx.shortcut(undo)];
+                let button = ctx.with_focus_scope(false, |ctx| {
+                    ctx.set_next_shortcut(undo);
+                    ctx.button(Vec2::zero(), Vec2::zero(), "Undo".into())
+                });
+                triggered.push(button.clicked);
+                if modal {
+                    let modal_save = ctx.with_focus_scope(true, |ctx| {
+                        ctx.button(Vec2::new(0, 50), Vec2::zero(), "OK".into());
+                        ctx.shortcut(save)
+                    });
+                    triggered.push(modal_save);
+                }
+                (triggered, button)
+            });
+            (result.new_state, triggered, result.commands, button)
+        };
+
+        let (state, triggered, commands, button) = frame(UIState::new(), save, false);
+        assert_eq!(triggered, [true, false, false]);
+        // the button grows to fit its accelerator text
+        assert!(commands.iter().any(|command| matches!(command,
+            DrawCommand::DrawText { content, draw_data, .. }
+                if content == "Ctrl+Shift+Z" && draw_data.role == UIDrawRole::ShortcutText)));
+        assert_eq!(
+            button.rect.size.x,
+            MOCK_TEXT_WIDTH * ("Undo".len() + "Ctrl+Shift+Z".len()) as u32 + SHORTCUT_GAP
+        );
+
+        // without focus in its scope the global undo wins, then the focused button does
+        let (mut state, triggered, ..) = frame(state, undo, false);
+        assert_eq!(triggered, [false, true, false]);
+        state.set_focused(Some(button.id));
+        let (state, ..) = frame(state, save, false);
+        let (state, triggered, ..) = frame(state, undo, false);
+        assert_eq!(triggered, [false, false, true]);
+
+        // an open modal blocks shortcuts outside it
+        let (state, ..) = frame(state, save, true);
+        let (_, triggered, ..) = frame(state, save, true);
+        assert_eq!(triggered, [false, false, false, true]);
+
+        // the same shortcut registered twice in one frame triggers once, remembered or not
+        let press = |state: UIState| {
+            let input = UIInputState {
+                key_events: vec![KeyEvent {
+                    key: Key::S,
+                    pressed: true,
+                    repeat: false,
+                    modifiers: Modifiers::CTRL,
+                }],
+                ..Default::default()
+            };
+            let mut ctx = UIContext::new(state, &font_info, input);
+            let triggered = [ctx.shortcut(save), ctx.shortcut(save)];
+            (ctx.end().new_state, triggered)
+        };
+        let (state, triggered) = press(UIState::new());
+        assert_eq!(triggered, [true, false]);
+        let (_, triggered) = press(state);
+        assert_eq!(triggered, [true, false]);
+
+        // a press the remembered winner wasn't drawn for goes to the next frame's winner
+        let draw = |state: UIState, pressed: bool, global: bool| {
+            let key_events = pressed.then_some(KeyEvent {
+                key: Key::S,
+                pressed: true,
+                repeat: false,
+                modifiers: Modifiers::CTRL,
+            });
+            let input = UIInputState {
+                key_events: key_events.into_iter().collect(),
+                ..Default::default()
+            };
+            let mut ctx = UIContext::new(state, &font_info, input);
+            let mut triggered = vec![];
+            if global {
+                triggered.push(ctx.shortcut(save));
+            }
+            triggered.push(ctx.with_focus_scope(false, |ctx| ctx.shortcut(save)));
+            (ctx.end().new_state, triggered)
+        };
+        let (state, _) = draw(UIState::new(), false, true);
+        let (state, triggered) = draw(state, true, false);
+        assert_eq!(triggered, [false]);
+        let (_, triggered) = draw(state, false, false);
+        assert_eq!(triggered, [true]);
+    }
+
+    #[test]
+    fn text_input_focuses_on_click_and_takes_typing() {
+        let font_info = mock_font_info();
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(200, 24),
+        };
+        let mut state = TextInputState::new("ac");
+        let between = Vec2::new(TEXT_INPUT_INSET + MOCK_TEXT_WIDTH + 1, 10);
+
+        // hover, then press between the two chars to focus the field and place the caret there
+        let mut ctx = UIContext::new(
+            UIState::new(),
+            &font_info,
+            UIInputState {
+                mouse_position: between,
+                ..Default::default()
+            },
+        );
+        ctx.text_input(rect, &mut state);
+        let ui_state = ctx.end().new_state;
+        let mut ctx = UIContext::new(
+            ui_state,
+            &font_info,
+            UIInputState {
+                mouse_position: between,
+                activate_button: ButtonState::Down,
+                ..Default::default()
+            },
+        );
+        assert!(!ctx.text_input(rect, &mut state).changed);
+        assert_eq!(state.caret, 1);
+        let ui_state = ctx.end().new_state;
+        assert!(ui_state.focused_id().is_some());
+
+        let mut ctx = UIContext::new(
+            ui_state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(999, 999),
+                typed_chars: vec!['b', '\n'],
+                ..Default::default()
+            },
+        );
+        assert!(ctx.text_input(rect, &mut state).changed);
+        assert_eq!(state.buffer, "abc");
+
+        let roles: Vec<UIDrawRole> = ctx
+            .command_buffer
+            .iter()
+            .filter_map(|cmd| match cmd {
+                DrawCommand::DrawRect { draw_data } => Some(draw_data.role),
+                DrawCommand::DrawText { draw_data, .. } => Some(draw_data.role),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                UIDrawRole::TextInputBackground,
+                UIDrawRole::TextInputText,
+                UIDrawRole::TextInputCaret
+            ]
+        );
+
+        // pressing outside the field blurs it
+        let ui_state = ctx.end().new_state;
+        let mut ctx = UIContext::new(
+            ui_state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(999, 999),
+                activate_button: ButtonState::Down,
+                typed_chars: vec!['d'],
+                ..Default::default()
+            },
+        );
+        assert!(!ctx.text_input(rect, &mut state).changed);
+        assert_eq!(ctx.end().new_state.focused_id(), None);
+    }
+
+    #[test]
+    fn text_area_wraps_moves_vertically_and_scrolls() {
+        let font_info = mock_font_info();
+        // room for 10 chars across and 2 lines down
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(MOCK_TEXT_WIDTH * 10 + 8, MOCK_TEXT_HEIGHT * 2 + 8),
+        };
+        let mut state = TextInputState::new("hello world again\nx");
+        state.set_caret(1, false);
+
+        let mut ctx = UIContext::new(UIState::new(), &font_info, UIInputState::default());
+        assert_eq!(
+            ctx.wrap_text(&state.buffer, rect.size.x - 8, 1.0),
+            vec![0..6, 6..12, 12..17, 18..19]
+        );
+
+        // focus it
+        let mut ui_state = UIState::new();
+        ui_state.focused = Some(ctx.make_id());
+        let mut frame = |input: UIInputState, state: &mut TextInputState| {
+            let (result, commands) = run_frame(std::mem::take(&mut ui_state), input, |ctx| {
+                ctx.text_area(rect, state);
+                ctx.command_buffer.clone()
+            });
+            ui_state = result.new_state;
+            commands
+        };
+
+        frame(
+            UIInputState {
+                move_down: ButtonState::Down,
+                ..Default::default()
+            },
+            &mut state,
+        );
+        assert_eq!(
+            state.caret, 7,
+            "caret should keep its column on the next line"
+        );
+
+        let commands = frame(
+            UIInputState {
+                page_down_button: ButtonState::Down,
+                ..Default::default()
+            },
+            &mut state,
+        );
+        assert_eq!(state.caret, 19, "caret should clamp to the last line");
+        assert_eq!(state.scroll_y, MOCK_TEXT_HEIGHT * 2);
+
+        // only the two scrolled-to lines are drawn
+        let texts: Vec<&str> = commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                DrawCommand::DrawText { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(texts, vec!["again", "x"]);
+
+        frame(
+            UIInputState {
+                typed_chars: vec!['\r', 'y'],
+                ..Default::default()
+            },
+            &mut state,
+        );
+        assert_eq!(state.buffer, "hello world again\nx\ny");
+        assert_eq!(state.scroll_y, MOCK_TEXT_HEIGHT * 3);
+    }
+
+    #[test]
+    fn scroll_area_scrolls_clips_and_hit_tests_content() {
+        let font_info = mock_font_info();
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(100, 50),
+        };
+        let mut scroll = ScrollState::default();
+        // five buttons 20px high
+        let draw_buttons = |ui: &mut UIContext| {
+            (0..5)
+                .map(|i| ui.button_layout(Vec2::new(0, 4), format!("B{i}")).clicked)
+                .collect::<Vec<bool>>()
+        };
+
+        // the first frame measures the content
+        let mut ctx = UIContext::new(UIState::new(), &font_info, UIInputState::default());
+        ctx.scroll_area(rect, &mut scroll, draw_buttons);
+        let state = ctx.end().new_state;
+        assert_eq!(scroll.content_size, Vec2::new(16, 100));
+
+        // scrolling the wheel down moves the content up
+        let mut ctx = UIContext::new(
+            state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(5, 10),
+                wheel_y: -1.0,
+                ..Default::default()
+            },
+        );
+        ctx.scroll_area(rect, &mut scroll, draw_buttons);
+        assert_eq!(scroll.offset, Vec2::new(0, 40));
+        let state = ctx.end().new_state;
+
+        // hover settles on the scrolled content a frame later
+        let mut ctx = UIContext::new(
+            state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(5, 10),
+                ..Default::default()
+            },
+        );
+        ctx.scroll_area(rect, &mut scroll, draw_buttons);
+        let state = ctx.end().new_state;
+
+        let mut ctx = UIContext::new(
+            state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(5, 10),
+                ..Default::default()
+            },
+        );
+        ctx.scroll_area(rect, &mut scroll, draw_buttons);
+        assert!(matches!(
+            ctx.command_buffer[2],
+            DrawCommand::PushClip {
+                rect: Rect {
+                    size: Vec2 { x: 90, y: 50 },
+                    ..
+                },
+                offset: Vec2 { x: 0, y: 40 }
+            }
+        ));
+        assert!(matches!(
+            ctx.command_buffer.back(),
+            Some(DrawCommand::PopClip)
+        ));
+        let hovered: Vec<usize> = ctx
+            .command_buffer
+            .iter()
+            .filter_map(|cmd| match cmd {
+                DrawCommand::DrawRect { draw_data } => Some(draw_data),
+                _ => None,
+            })
+            .filter(|data| data.role == UIDrawRole::ButtonBackground)
+            .enumerate()
+            .filter(|(_, data)| data.flags.contains(flags::HOVER))
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(
+            hovered,
+            vec![2],
+            "the mouse should hit the scrolled content"
+        );
+        let state = ctx.end().new_state;
+
+        // content scrolled past the bottom of the view can't be clicked
+        let mut ctx = UIContext::new(
+            state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(5, 60),
+                activate_button: ButtonState::Down,
+                ..Default::default()
+            },
+        );
+        ctx.scroll_area(rect, &mut scroll, draw_buttons);
+        let state = ctx.end().new_state;
+        let mut ctx = UIContext::new(
+            state,
+            &font_info,
+            UIInputState {
+                mouse_position: Vec2::new(5, 60),
+                activate_button: ButtonState::Released,
+                ..Default::default()
+            },
+        );
+        let clicked = ctx.scroll_area(rect, &mut scroll, draw_buttons);
+        assert!(clicked.iter().all(|c| !c));
+    }
+
+    #[test]
+    fn layers_draw_in_order_and_topmost_takes_hover() {
+        let frame = |state: UIState| {
+            let input_state = UIInputState {
+                mouse_position: Vec2::new(5, 5),
+                ..Default::default()
+            };
+            let (result, _) = run_frame(state, input_state, |ctx| {
+                ctx.with_clip(
+                    Rect {
+                        top_left: Vec2::new(100, 100),
+                        size: Vec2::new(10, 10),
+                    },
+                    |ctx| {
+                        // outside the clip, but layers draw unclipped
+                        ctx.with_layer(Layer::Popup, |ctx| {
+                            assert_eq!(ctx.current_layer(), Layer::Popup);
+                            assert_eq!(ctx.clip_rect(), None);
+                            ctx.button(Vec2::zero(), Vec2::new(4, 4), "Top".into());
+                        });
+                    },
+                );
+                assert_eq!(ctx.current_layer(), Layer::Default);
+                ctx.button(Vec2::zero(), Vec2::new(4, 4), "Under".into());
+                ctx.with_layer(Layer::Background, |ctx| {
+                    ctx.rect_raw(
+                        Rect {
+                            top_left: Vec2::zero(),
+                            size: Vec2::new(50, 50),
+                        },
+                        flags::NONE,
+                        UIDrawRole::LayoutBackground,
+                    );
+                });
+            });
+            let draws: Vec<(UIDrawRole, Flags)> = result
+                .commands
+                .iter()
+                .filter_map(|cmd| match cmd {
+                    DrawCommand::DrawRect { draw_data }
+                    | DrawCommand::DrawText { draw_data, .. } => {
+                        Some((draw_data.role, draw_data.flags))
+                    }
+                    _ => None,
+                })
+                .collect();
+            (result.new_state, result.commands, draws)
+        };
+
+        let (state, commands, draws) = frame(UIState::new());
+        assert_eq!(draws[0].0, UIDrawRole::LayoutBackground);
+        assert_eq!(draws[3].0, UIDrawRole::ButtonBackground);
+        assert!(matches!(commands[1], DrawCommand::PushClip { .. }));
+        assert!(matches!(commands[2], DrawCommand::PopClip));
+        assert!(state.hovered_id().is_some());
+
+        // the popup button takes hover even though the default layer drew later
+        let (_, _, draws) = frame(state);
+        let button_flags: Vec<Flags> = draws
+            .iter()
+            .filter(|(role, _)| *role == UIDrawRole::ButtonBackground)
+            .map(|(_, flags)| *flags)
+            .collect();
+        assert!(!button_flags[0].contains(flags::HOVER), "under");
+        assert!(button_flags[1].contains(flags::HOVER), "top");
+    }
+
+    #[test]
+    fn covered_widgets_dont_hover_or_click() {
+        let frame = |state: UIState, mouse_position: Vec2, activate_button: ButtonState| {
+            let input_state = UIInputState {
+                mouse_position,
+                activate_button,
+                ..Default::default()
+            };
+            let (result, (under, top)) = run_frame(state, input_state, |ctx| {
+                let under = ctx
+                    .button(Vec2::new(50, 5), Vec2::new(4, 4), "A".into())
+                    .clicked;
+                // a panel drawn over the first button, holding another
+                let top = ctx.layout_at(Vec2::zero(), LayoutDirection::Vertical, 0, true, |ctx| {
+                    let top = ctx.button_layout(Vec2::new(4, 4), "B".into()).clicked;
+                    ctx.recompute_current_layout(Vec2::new(100, 40));
+                    top
+                });
+                (under, top)
+            });
+            (result.new_state, under, top)
+        };
+        let click = |mouse_position: Vec2| {
+            let mut state = UIState::new();
+            let mut clicks = vec![];
+            for button in [ButtonState::Up, ButtonState::Down, ButtonState::Released] {
+                let (new_state, under, top) = frame(state, mouse_position, button);
+                state = new_state;
+                clicks.push((under, top));
+            }
+            (state, clicks)
+        };
+
+        // the panel covers the first button
+        let (state, clicks) = click(Vec2::new(55, 10));
+        assert!(clicks.iter().all(|(under, top)| !under && !top));
+        assert!(state.hovered_id().is_some(), "the panel should take hover");
+
+        // the panel's own children still hover and click
+        let (_, clicks) = click(Vec2::new(5, 5));
+        assert_eq!(clicks.last(), Some(&(false, true)));
     }
 
     #[test]
-    fn layout_at_uses_given_position_for_background() {
+    fn clip_blocks_hover_outside_and_nests() {
         let font_info = mock_font_info();
-        let mut ctx = super::UIContext::new(UIState::new(), &font_info, UIInputState::default());
+        let input_state = UIInputState {
+            mouse_position: Vec2::new(30, 5),
+            ..Default::default()
+        };
+        let mut ctx = UIContext::new(UIState::new(), &font_info, input_state);
+        let clip = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(20, 20),
+        };
 
-        let layout_pos = Vec2 { x: 20, y: 30 };
-        ctx.layout_at(layout_pos, LayoutDirection::Vertical, 2, true, |ctx| {
-            ctx.text_layout("abc".into())
-        });
+        ctx.with_clip(clip, |ctx| {
+            // the button pokes out of the clip under the mouse
+            ctx.button(Vec2::zero(), Vec2::new(32, 4), "A".into());
 
-        assert_eq!(ctx.command_buffer.len(), 2);
-        match &ctx.command_buffer[0] {
-            DrawCommand::DrawRect { draw_data } => {
-                assert_eq!(draw_data.rect.top_left, layout_pos);
+            let inner = Rect {
+                top_left: Vec2::new(10, 10),
+                size: Vec2::new(20, 20),
+            };
+            ctx.with_clip(inner, |ctx| {
                 assert_eq!(
-                    draw_data.rect.size,
-                    Vec2 {
-                        x: MOCK_TEXT_WIDTH * 3,
-                        y: MOCK_TEXT_HEIGHT
-                    }
+                    ctx.clip_rect(),
+                    Some(Rect {
+                        top_left: Vec2::new(10, 10),
+                        size: Vec2::new(10, 10),
+                    })
                 );
+            });
+        });
+        assert_eq!(ctx.clip_rect(), None);
+
+        match &ctx.command_buffer[1] {
+            DrawCommand::DrawRect { draw_data } => {
+                assert_eq!(draw_data.role, UIDrawRole::ButtonBackground);
+                assert!(!draw_data.flags.contains(flags::HOVER));
             }
-            _ => panic!("expected layout background to be a rect draw"),
+            _ => panic!("expected the button background after the clip"),
         }
+        assert!(matches!(
+            ctx.command_buffer[0],
+            DrawCommand::PushClip { rect, .. } if rect == clip
+        ));
+        assert!(matches!(ctx.command_buffer[4], DrawCommand::PopClip));
+        assert!(matches!(ctx.command_buffer[5], DrawCommand::PopClip));
     }
 
     #[test]
-    fn button_click() {
-        let font_info = mock_font_info();
-        let ui_state = UIState::new();
+    fn scroll_area_follows_keyboard_focus() {
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(100, 50),
+        };
+        let mut scroll = ScrollState::default();
+        let draw_buttons = |ui: &mut UIContext| {
+            for i in 0..5 {
+                ui.button_layout(Vec2::new(0, 4), format!("B{i}"));
+            }
+        };
 
-        // first frame: mouse down over button
-        let mut ctx = super::UIContext::new(
-            ui_state,
-            &font_info,
-            UIInputState {
-                activate_button: ButtonState::Down,
+        let mut state = UIState::new();
+        for _ in 0..4 {
+            let tab = UIInputState {
+                focus_next_button: ButtonState::Released,
                 ..Default::default()
-            },
-        );
-        let clicked = ctx.button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into());
-        assert!(!clicked, "button should not register click on mouse down");
-        let result = ctx.end();
+            };
+            let (result, _) = run_frame(state, tab, |ctx| {
+                ctx.scroll_area(rect, &mut scroll, draw_buttons);
+            });
+            state = result.new_state;
+        }
 
-        // second frame: mouse up over button
-        let mut ctx = super::UIContext::new(
-            result.new_state,
-            &font_info,
-            UIInputState {
-                activate_button: ButtonState::Released,
-                ..Default::default()
-            },
-        );
-        let clicked = ctx.button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into());
-        assert!(clicked, "button should register click on mouse up");
+        // the fourth button (60px..80px) is focused and scrolled to on the next frame
+        run_frame(state, UIInputState::default(), |ctx| {
+            ctx.scroll_area(rect, &mut scroll, draw_buttons);
+        });
+        assert_eq!(scroll.offset.y, 30);
     }
 
     #[test]
-    fn button_click_outside() {
-        let font_info = mock_font_info();
-        let ui_state = UIState::new();
+    fn scroll_area_scrolls_up_to_focused_child_above_view() {
+        let rect = Rect {
+            top_left: Vec2::new(0, 20),
+            size: Vec2::new(100, 50),
+        };
+        let mut scroll = ScrollState {
+            offset: Vec2::new(0, 40),
+            content_size: Vec2::new(100, 200),
+        };
+        // the button starts above the view's top-left
+        let draw = |ui: &mut UIContext| {
+            ui.button(Vec2::new(0, 10), Vec2::new(0, 4), "Above".into());
+            ui.recompute_current_layout(Vec2::new(100, 200));
+        };
 
-        let input_state = UIInputState {
-            mouse_position: Vec2 { x: 100, y: 100 },
-            activate_button: ButtonState::Down,
+        let tab = UIInputState {
+            focus_next_button: ButtonState::Released,
             ..Default::default()
         };
+        let (result, _) = run_frame(UIState::new(), tab, |ctx| {
+            ctx.scroll_area(rect, &mut scroll, draw);
+        });
+        run_frame(result.new_state, UIInputState::default(), |ctx| {
+            ctx.scroll_area(rect, &mut scroll, draw);
+        });
+        assert_eq!(scroll.offset.y, 0);
+    }
 
-        // first frame: mouse down outside button
-        let mut ctx = super::UIContext::new(ui_state, &font_info, input_state);
-        let clicked = ctx.button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into());
-        assert!(
-            !clicked,
-            "button should not register click on mouse down outside"
-        );
-        let result = ctx.end();
-
-        let input_state = UIInputState {
-            mouse_position: Vec2 { x: 100, y: 100 },
-            ..Default::default()
+    #[test]
+    fn wheel_steps_hovered_slider_instead_of_scrolling() {
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(100, 50),
+        };
+        let mut scroll = ScrollState {
+            offset: Vec2::zero(),
+            content_size: Vec2::new(100, 200),
+        };
+        let mut slider_state = SliderState::new(0_u32, 10_u32, 5_u32, 1_u32);
+        let mut frame = |state: UIState, wheel_y: f32, slider_state: &mut SliderState<u32>| {
+            let input = UIInputState {
+                mouse_position: Vec2::new(5, 5),
+                wheel_y,
+                ..Default::default()
+            };
+            let (result, _) = run_frame(state, input, |ctx| {
+                ctx.scroll_area(rect, &mut scroll, |ui| {
+                    ui.slider_layout(Vec2::new(50, 10), slider_state);
+                    ui.recompute_current_layout(Vec2::new(100, 200));
+                });
+            });
+            result.new_state
         };
 
-        // second frame: mouse up outside button
-        let mut ctx = super::UIContext::new(result.new_state, &font_info, input_state);
-        let clicked = ctx.button(Vec2 { x: 0, y: 0 }, Vec2 { x: 8, y: 4 }, "Click me".into());
-        assert!(
-            !clicked,
-            "button should not register click on mouse up outside"
-        );
+        let state = frame(UIState::new(), 0.0, &mut slider_state);
+        let state = frame(state, 1.0, &mut slider_state);
+        assert_eq!(slider_state.value, 6);
+
+        // fractional movement accumulates into whole steps
+        let state = frame(state, -0.6, &mut slider_state);
+        assert_eq!(slider_state.value, 6);
+        frame(state, -0.6, &mut slider_state);
+        assert_eq!(slider_state.value, 5);
+
+        // the slider used the wheel, so the content didn't scroll
+        assert_eq!(scroll.offset, Vec2::zero());
     }
 
     #[test]
-    fn enter_key_activates_focused_button() {
+    fn scroll_area_thumb_drags_content() {
         let font_info = mock_font_info();
-        let button_padding = Vec2::zero();
-        let button_pos = Vec2::zero();
-        let mouse_far = Vec2 { x: 999, y: 999 };
+        let rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(100, 50),
+        };
+        // content is twice as tall as the view, so the thumb is half the track
+        let mut scroll = ScrollState {
+            offset: Vec2::zero(),
+            content_size: Vec2::new(50, 100),
+        };
+        let draw_content = |ui: &mut UIContext| {
+            ui.recompute_current_layout(Vec2::new(50, 100));
+        };
+        let thumb_pos = Vec2::new(95, 5);
 
-        // focus the button
-        let mut ctx = super::UIContext::new(
+        let mut ctx = UIContext::new(
             UIState::new(),
             &font_info,
             UIInputState {
-                focus_next_button: ButtonState::Released,
-                mouse_position: mouse_far,
-                ..Default::default()
-            },
-        );
-        ctx.button(button_pos, button_padding, "A".into());
-        let result = ctx.end();
-
-        // key down should mark it active but not click yet
-        let mut ctx = super::UIContext::new(
-            result.new_state,
-            &font_info,
-            UIInputState {
+                mouse_position: thumb_pos,
                 activate_button: ButtonState::Down,
-                mouse_position: mouse_far,
                 ..Default::default()
             },
         );
-        let clicked = ctx.button(button_pos, button_padding, "A".into());
-        assert!(
-            !clicked,
-            "activate key down alone should not register a click"
-        );
+        ctx.scroll_area(rect, &mut scroll, draw_content);
         let state = ctx.end().new_state;
 
-        // releasing the key should click the focused button even without hover
-        let mut ctx = super::UIContext::new(
+        // moving the thumb 10px moves the content 20px
+        let mut ctx = UIContext::new(
             state,
             &font_info,
             UIInputState {
-                activate_button: ButtonState::Released,
-                mouse_position: mouse_far,
+                mouse_position: Vec2::add(thumb_pos, Vec2::new(0, 10)),
+                activate_button: ButtonState::Held,
                 ..Default::default()
             },
         );
-        let clicked = ctx.button(button_pos, button_padding, "A".into());
-        assert!(clicked, "activate key up should click the focused button");
+        ctx.scroll_area(rect, &mut scroll, draw_content);
+        assert_eq!(scroll.offset.y, 20);
     }
 
     #[test]
-    fn tab_focus_advances_through_focusables() {
-        let font_info = mock_font_info();
-        let button_padding = Vec2::zero();
-        let first_button_pos = Vec2::zero();
-        let second_button_pos = Vec2 { x: 50, y: 0 };
+    fn radio_selects_on_click_and_arrows_move_within_group() {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        enum Quality {
+            Low,
+            Medium,
+            High,
+        }
 
-        // first tab press should focus the first registered control
-        let mut ctx = super::UIContext::new(
+        let size = Vec2::new(10, 10);
+        let mut quality = Quality::Low;
+        let mut other = 0;
+        let mut frame = |state: UIState, input: UIInputState, quality: &mut Quality| {
+            let (result, selected) = run_frame(state, input, |ctx| {
+                let selected = [Quality::Low, Quality::Medium, Quality::High]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, option)| {
+                        ctx.radio(Vec2::new(i as u32 * 20, 0), size, quality, option)
+                            .changed
+                    })
+                    .collect::<Vec<bool>>();
+                // a separate group
+                ctx.radio(Vec2::new(0, 50), size, &mut other, 1);
+                selected
+            });
+            (result.new_state, selected)
+        };
+
+        // click the second radio
+        let click_at = Vec2::new(25, 5);
+        let (state, _) = frame(
             UIState::new(),
-            &font_info,
             UIInputState {
-                focus_next_button: ButtonState::Released,
+                mouse_position: click_at,
+                activate_button: ButtonState::Down,
                 ..Default::default()
             },
+            &mut quality,
         );
-        ctx.button(first_button_pos, button_padding, "A".into());
-        ctx.button(second_button_pos, button_padding, "B".into());
-        let result = ctx.end();
-        assert_eq!(result.new_state.focused.unwrap().top_left, first_button_pos);
+        let (state, selected) = frame(
+            state,
+            UIInputState {
+                mouse_position: click_at,
+                activate_button: ButtonState::Released,
+                ..Default::default()
+            },
+            &mut quality,
+        );
+        assert_eq!(selected, vec![false, true, false]);
+        assert_eq!(quality, Quality::Medium);
 
-        // next press should advance to the next focusable
-        let mut ctx = super::UIContext::new(
-            result.new_state,
-            &font_info,
+        // focus the first radio, then arrow forward twice and wrap around
+        let (mut state, _) = frame(
+            state,
             UIInputState {
                 focus_next_button: ButtonState::Released,
                 ..Default::default()
             },
+            &mut quality,
         );
-        ctx.button(first_button_pos, button_padding, "A".into());
-        ctx.button(second_button_pos, button_padding, "B".into());
-        let result = ctx.end();
-        assert_eq!(
-            result.new_state.focused.unwrap().top_left,
-            second_button_pos
-        );
+        for expected in [Quality::Medium, Quality::High, Quality::Low] {
+            (state, _) = frame(
+                state,
+                UIInputState {
+                    move_down: ButtonState::Down,
+                    ..Default::default()
+                },
+                &mut quality,
+            );
+            // selection follows focus when the radio is next drawn
+            (state, _) = frame(state, UIInputState::default(), &mut quality);
+            assert_eq!(quality, expected);
+        }
+        assert_eq!(other, 0, "other groups should be untouched");
+    }
 
-        // pressing again should wrap back to the first
-        let mut ctx = super::UIContext::new(
-            result.new_state,
-            &font_info,
+    #[test]
+    fn radio_groups_at_the_same_address_stay_apart() {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        #[repr(C)]
+        struct Settings {
+            level: u8,
+            quality: u8,
+        }
+
+        let size = Vec2::new(10, 10);
+        let low = Settings {
+            level: 0,
+            quality: 0,
+        };
+        let high = Settings { quality: 1, ..low };
+        let mut settings = low;
+        let frame = |state: UIState, input: UIInputState, settings: &mut Settings| {
+            let (result, _) = run_frame(state, input, |ctx| {
+                ctx.radio(Vec2::new(0, 0), size, settings, low);
+                ctx.radio(Vec2::new(20, 0), size, settings, high);
+                // the first field lives at the same address as the whole struct
+                ctx.radio(Vec2::new(0, 50), size, &mut settings.level, 5);
+                ctx.radio(Vec2::new(20, 50), size, &mut settings.level, 6);
+            });
+            result.new_state
+        };
+
+        let mut state = frame(
+            UIState::new(),
             UIInputState {
                 focus_next_button: ButtonState::Released,
                 ..Default::default()
             },
+            &mut settings,
         );
-        ctx.button(first_button_pos, button_padding, "A".into());
-        ctx.button(second_button_pos, button_padding, "B".into());
-        let result = ctx.end();
-        assert_eq!(result.new_state.focused.unwrap().top_left, first_button_pos);
+        for expected in [high, low] {
+            state = frame(
+                state,
+                UIInputState {
+                    move_right: ButtonState::Down,
+                    ..Default::default()
+                },
+                &mut settings,
+            );
+            state = frame(state, UIInputState::default(), &mut settings);
+            assert_eq!(
+                settings, expected,
+                "arrows should wrap within the struct's group"
+            );
+        }
     }
 
     #[test]
@@ -1409,6 +6720,242 @@ mod test {
         assert_eq!(slider_state.value, slider_state.min);
     }
 
+    #[test]
+    fn combo_box_opens_popup_above_content_and_picks() {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        enum Shape {
+            Circle,
+            Square,
+            Star,
+        }
+        let options = [
+            (Shape::Circle, "Circle"),
+            (Shape::Square, "Square"),
+            (Shape::Star, "Star"),
+        ];
+
+        let combo_rect = Rect {
+            top_left: Vec2::zero(),
+            size: Vec2::new(80, 20),
+        };
+        let mut shape = Shape::Circle;
+        let mut button_clicks = 0;
+        let mut frame = |state: UIState, input: UIInputState, shape: &mut Shape| {
+            let (result, changed) = run_frame(state, input, |ctx| {
+                let changed = ctx.combo_box(combo_rect, shape, &options).changed;
+                // sits under the popup's last item
+                if ctx
+                    .button(Vec2::new(0, 60), Vec2::new(4, 4), "Under".into())
+                    .clicked
+                {
+                    button_clicks += 1;
+                }
+                changed
+            });
+            (result.new_state, result.commands, changed)
+        };
+        let press = |mouse_position: Vec2, activate_button: ButtonState| UIInputState {
+            mouse_position,
+            activate_button,
+            ..Default::default()
+        };
+        let on_combo = Vec2::new(5, 5);
+
+        // click to open
+        let (state, _, _) = frame(
+            UIState::new(),
+            press(on_combo, ButtonState::Down),
+            &mut shape,
+        );
+        let (state, commands, _) = frame(state, press(on_combo, ButtonState::Released), &mut shape);
+        assert_eq!(state.focused_id(), state.open_popup);
+        let roles: Vec<UIDrawRole> = commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                DrawCommand::DrawRect { draw_data } | DrawCommand::DrawText { draw_data, .. } => {
+                    Some(draw_data.role)
+                }
+                _ => None,
+            })
+            .collect();
+        // the popup is drawn after the button beneath it
+        assert_eq!(roles[3], UIDrawRole::ButtonBackground);
+        assert_eq!(roles[5], UIDrawRole::PopupBackground);
+        assert_eq!(roles.last(), Some(&UIDrawRole::PopupItemText));
+
+        // arrow down and activate picks the highlighted option
+        let (state, _, _) = frame(
+            state,
+            UIInputState {
+                mouse_position: on_combo,
+                move_down: ButtonState::Down,
+                ..Default::default()
+            },
+            &mut shape,
+        );
+        let (state, _, _) = frame(state, press(on_combo, ButtonState::Down), &mut shape);
+        let (state, _, changed) = frame(state, press(on_combo, ButtonState::Released), &mut shape);
+        assert!(changed);
+        assert_eq!(shape, Shape::Square);
+        assert_eq!(state.open_popup, None);
+
+        // reopen and click the last item, which covers the button
+        let (state, _, _) = frame(state, press(on_combo, ButtonState::Down), &mut shape);
+        let (state, _, _) = frame(state, press(on_combo, ButtonState::Released), &mut shape);
+        let on_star = Vec2::new(5, 65);
+        let (state, _, _) = frame(state, press(on_star, ButtonState::Down), &mut shape);
+        let (state, _, changed) = frame(state, press(on_star, ButtonState::Released), &mut shape);
+        assert!(changed);
+        assert_eq!(shape, Shape::Star);
+        assert_eq!(state.open_popup, None);
+
+        // cancel and outside clicks close without changing the value
+        let (state, _, _) = frame(state, press(on_combo, ButtonState::Down), &mut shape);
+        let (state, _, _) = frame(state, press(on_combo, ButtonState::Released), &mut shape);
+        let (state, _, _) = frame(
+            state,
+            UIInputState {
+                cancel_button: ButtonState::Down,
+                ..Default::default()
+            },
+            &mut shape,
+        );
+        assert_eq!(state.open_popup, None);
+        assert!(
+            state.focused_id().is_some(),
+            "closing the popup keeps focus"
+        );
+        let (state, _, _) = frame(state, press(on_combo, ButtonState::Down), &mut shape);
+        let (state, _, _) = frame(state, press(on_combo, ButtonState::Released), &mut shape);
+        let (state, _, changed) = frame(
+            state,
+            press(Vec2::new(200, 200), ButtonState::Down),
+            &mut shape,
+        );
+        assert!(!changed);
+        assert_eq!(state.open_popup, None);
+        assert_eq!(shape, Shape::Star);
+        assert_eq!(button_clicks, 0);
+    }
+
+    #[test]
+    fn flags_are_distinct_and_focus_differs_from_press() {
+        let all = [
+            flags::HOVER,
+            flags::DISABLED,
+            flags::ACTIVE,
+            flags::FOCUSED,
+            flags::CHECKED,
+            flags::SELECTED,
+            flags::OPEN,
+            flags::DRAGGING,
+        ];
+        let combined = all.iter().fold(flags::NONE, |acc, flag| acc | *flag);
+        assert_eq!(combined.bits().count_ones(), all.len() as u32);
+        assert_eq!(combined.iter().collect::<Vec<Flags>>(), all);
+
+        let mut set = flags::HOVER | flags::FOCUSED;
+        assert!(set.contains(flags::FOCUSED));
+        assert!(!set.contains(flags::FOCUSED | flags::ACTIVE));
+        assert!(set.intersects(flags::FOCUSED | flags::ACTIVE));
+        set.insert(flags::ACTIVE);
+        set.remove(flags::HOVER);
+        assert_eq!(format!("{set:?}"), "ACTIVE | FOCUSED");
+        assert_eq!(format!("{:?}", flags::NONE), "NONE");
+
+        // a focused button only reads as active while pressed
+        let font_info = mock_font_info();
+        let button_flags = |activate_button: ButtonState| {
+            let mut ui_state = UIState::new();
+            let mut ctx = UIContext::new(UIState::new(), &font_info, UIInputState::default());
+            ui_state.focused = Some(ctx.make_id());
+            if activate_button.is_down() {
+                ui_state.active = ui_state.focused;
+            }
+            let mut ctx = UIContext::new(
+                ui_state,
+                &font_info,
+                UIInputState {
+                    mouse_position: Vec2::new(999, 999),
+                    activate_button,
+                    ..Default::default()
+                },
+            );
+            ctx.button(Vec2::zero(), Vec2::new(4, 4), "A".into());
+            match &ctx.command_buffer[0] {
+                DrawCommand::DrawRect { draw_data } => draw_data.flags,
+                _ => panic!("expected the button background"),
+            }
+        };
+        assert_eq!(button_flags(ButtonState::Up), flags::FOCUSED);
+        assert_eq!(
+            button_flags(ButtonState::Down),
+            flags::FOCUSED | flags::ACTIVE
+        );
+        // held past the press, it's dragging like its response says
+        assert_eq!(
+            button_flags(ButtonState::Held),
+            flags::FOCUSED | flags::ACTIVE | flags::DRAGGING
+        );
+    }
+
+    #[test]
+    fn disabled_widgets_draw_but_ignore_input_and_focus() {
+        let mut checked = false;
+        let mut frame = |state: UIState, input: UIInputState, disabled: bool| {
+            let (result, (first, toggled)) = run_frame(state, input, |ctx| {
+                let first = ctx
+                    .button(Vec2::zero(), Vec2::new(4, 4), "A".into())
+                    .clicked;
+                let toggled = ctx.with_disabled(disabled, |ctx| {
+                    // an enabled scope can't re-enable widgets
+                    ctx.with_disabled(false, |ctx| {
+                        ctx.checkbox(Vec2::new(0, 30), Vec2::new(20, 20), &mut checked)
+                            .changed
+                    })
+                });
+                (first, toggled)
+            });
+            (result.new_state, result.commands, first, toggled)
+        };
+        let on_checkbox = |activate_button: ButtonState| UIInputState {
+            mouse_position: Vec2::new(10, 40),
+            activate_button,
+            ..Default::default()
+        };
+
+        let mut state = UIState::new();
+        for button in [ButtonState::Up, ButtonState::Down, ButtonState::Released] {
+            let (new_state, commands, _, toggled) = frame(state, on_checkbox(button), true);
+            state = new_state;
+            assert!(!toggled);
+            match &commands[2] {
+                DrawCommand::DrawRect { draw_data } => {
+                    assert_eq!(draw_data.role, UIDrawRole::CheckboxBox);
+                    assert_eq!(draw_data.flags, flags::DISABLED);
+                }
+                _ => panic!("expected the checkbox after the button"),
+            }
+        }
+
+        // tab traversal skips the disabled checkbox
+        let tab = || UIInputState {
+            focus_next_button: ButtonState::Released,
+            ..Default::default()
+        };
+        let (state, _, _, _) = frame(UIState::new(), tab(), true);
+        let first = state.focused_id();
+        let (state, _, _, _) = frame(state, tab(), true);
+        assert_eq!(state.focused_id(), first);
+
+        // once enabled it can be focused, and disabling it again drops focus
+        let (state, _, _, _) = frame(state, tab(), false);
+        assert_ne!(state.focused_id(), first);
+        let (state, _, _, _) = frame(state, UIInputState::default(), true);
+        assert_eq!(state.focused_id(), None);
+        assert!(!checked);
+    }
+
     #[test]
     fn checkbox_toggles_and_draws_check() {
         let font_info = mock_font_info();
@@ -1428,7 +6975,7 @@ mod test {
                 ..Default::default()
             },
         );
-        let toggled = ctx.checkbox(rect.top_left, rect.size, &mut checked);
+        let toggled = ctx.checkbox(rect.top_left, rect.size, &mut checked).changed;
         assert!(!toggled);
         assert!(!checked);
         let state = ctx.end().new_state;
@@ -1443,7 +6990,7 @@ mod test {
                 ..Default::default()
             },
         );
-        let toggled = ctx.checkbox(rect.top_left, rect.size, &mut checked);
+        let toggled = ctx.checkbox(rect.top_left, rect.size, &mut checked).changed;
         assert!(toggled);
         assert!(checked);
 
@@ -1460,6 +7007,7 @@ mod test {
             ) => {
                 assert_eq!(box_draw.role, UIDrawRole::CheckboxBox);
                 assert_eq!(check_draw.role, UIDrawRole::CheckboxCheck);
+                assert!(box_draw.flags.contains(flags::CHECKED));
             }
             _ => panic!("expected two rectangle draws for checkbox"),
         }